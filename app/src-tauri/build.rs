@@ -1,3 +1,9 @@
 fn main() {
+  // Sidecars are named `contam_engine-<target-triple>` before bundling
+  println!(
+    "cargo:rustc-env=TARGET_TRIPLE={}",
+    std::env::var("TARGET").expect("cargo sets TARGET for build scripts")
+  );
+  #[cfg(feature = "gui")]
   tauri_build::build()
 }
@@ -0,0 +1,163 @@
+//! Content-addressed keys for reusing stored runs.
+//!
+//! A run is reusable when the engine would see exactly the same thing: the
+//! same model (ignoring key order and whitespace), the same solver and the
+//! same engine version. Matching runs are looked up in the run history.
+//!
+//! Runs of the same model in a looser sense, the same airflow network with
+//! possibly different parameters, share a `model_key` instead.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::options::RunOptions;
+
+/// SHA-256 over the canonical input, solver method and engine version, as hex.
+///
+/// Returns `None` when the run must not be served from the cache: the input
+/// is not valid JSON, the engine version is unknown, or the run writes an
+/// HDF5 file as a side effect.
+pub fn cache_key(input: &str, options: &RunOptions, engine_version: Option<&str>) -> Option<String> {
+    if options.hdf5_output.is_some() {
+        return None;
+    }
+    let engine_version = engine_version?;
+    let value: Value = serde_json::from_str(input).ok()?;
+
+    let mut canonical = String::with_capacity(input.len());
+    write_canonical(&value, &mut canonical);
+
+    let mut hasher = Sha256::new();
+    hasher.update(canonical.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(options.method.flag().as_bytes());
+    hasher.update(b"\0");
+    hasher.update(engine_version.as_bytes());
+    Some(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// SHA-256 over the network layout as hex: node IDs and link endpoints, but
+/// not element parameters, temperatures or schedules. Edits to a model that
+/// keep its network keep its key. `None` if the input has no such layout.
+pub fn model_key(input: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(input).ok()?;
+    let mut nodes: Vec<i64> = value["nodes"].as_array()?.iter().filter_map(|n| n["id"].as_i64()).collect();
+    let mut links: Vec<[i64; 3]> = value["links"]
+        .as_array()?
+        .iter()
+        .filter_map(|l| Some([l["id"].as_i64()?, l["from"].as_i64()?, l["to"].as_i64()?]))
+        .collect();
+    nodes.sort_unstable();
+    links.sort_unstable();
+
+    let mut hasher = Sha256::new();
+    hasher.update((nodes.len() as u64).to_le_bytes());
+    for id in nodes {
+        hasher.update(id.to_le_bytes());
+    }
+    for link in links.iter().flatten() {
+        hasher.update(link.to_le_bytes());
+    }
+    Some(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Compact JSON with object keys sorted, independent of how the frontend
+/// happened to order them.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::options::SolverMethod;
+    use crate::testing::case_input;
+
+    const VERSION: Option<&str> = Some("1.2.0");
+
+    fn key(input: &str) -> Option<String> {
+        cache_key(input, &RunOptions::default(), VERSION)
+    }
+
+    #[test]
+    fn key_order_and_whitespace_do_not_change_the_key() {
+        let compact = r#"{"nodes":[{"id":1,"name":"A"}],"ambient":{"temperature":293.15}}"#;
+        let reordered = r#"{
+            "ambient": { "temperature": 293.15 },
+            "nodes": [ { "name": "A", "id": 1 } ]
+        }"#;
+        assert_eq!(key(compact), key(reordered));
+        assert_eq!(key(compact).unwrap().len(), 64);
+
+        // Array order is part of the model
+        let swapped = r#"{"nodes":[{"id":2},{"id":1}]}"#;
+        assert_ne!(key(r#"{"nodes":[{"id":1},{"id":2}]}"#), key(swapped));
+    }
+
+    #[test]
+    fn solver_and_engine_version_are_part_of_the_key() {
+        let input = case_input("case01_3room");
+        let relaxed = RunOptions { method: SolverMethod::SubRelaxation, ..RunOptions::default() };
+        assert_ne!(key(&input), cache_key(&input, &relaxed, VERSION));
+        assert_ne!(key(&input), cache_key(&input, &RunOptions::default(), Some("1.3.0")));
+    }
+
+    #[test]
+    fn uncacheable_runs_have_no_key() {
+        let input = case_input("case01_3room");
+        let hdf5 = RunOptions { hdf5_output: Some("/tmp/out.h5".into()), ..RunOptions::default() };
+        assert!(cache_key(&input, &hdf5, VERSION).is_none(), "the HDF5 file is a side effect");
+        assert!(cache_key(&input, &RunOptions::default(), None).is_none());
+        assert!(key("{not json").is_none());
+    }
+
+    #[test]
+    fn model_key_follows_the_network_only() {
+        let mut model = json!({
+            "nodes": [{ "id": 1, "temperature": 293.15 }, { "id": 2, "temperature": 293.15 }],
+            "links": [{ "id": 10, "from": 1, "to": 2, "element": "door" }],
+        });
+        let original = model_key(&model.to_string()).unwrap();
+
+        model["nodes"][1]["temperature"] = json!(300.0);
+        model["links"][0]["element"] = json!("crack");
+        assert_eq!(model_key(&model.to_string()).unwrap(), original, "parameters are not part of the network");
+
+        model["nodes"].as_array_mut().unwrap().reverse();
+        assert_eq!(model_key(&model.to_string()).unwrap(), original, "neither is the listing order");
+
+        model["links"][0]["to"] = json!(1);
+        assert_ne!(model_key(&model.to_string()).unwrap(), original, "rewiring a link changes the network");
+
+        assert!(model_key(r#"{"nodes":[]}"#).is_none());
+        assert!(model_key("{not json").is_none());
+    }
+}
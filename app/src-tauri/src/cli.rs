@@ -0,0 +1,660 @@
+//! `airsim`: the backend without the app, for scripted runs and nightly
+//! regressions on machines without a display.
+//!
+//! Exit codes follow the engine's: 0 when everything succeeded, 1 for invalid
+//! models and failed runs, 2 when a solve did not converge or a run stopped
+//! early. `batch` exits with the worst code of its models.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::discovery::{self, EnginePath, EngineSource};
+use crate::engine::RunOutcome;
+use crate::error::EngineError;
+use crate::export::{self, ExportFormat};
+use crate::history::HistoryStore;
+use crate::launch::{Launcher, ProgressHook, RunRequest};
+use crate::limits::RunLimits;
+use crate::lint::{self, Severity};
+use crate::options::{RunOptions, SolverMethod, Verbosity};
+use crate::progress::Progress;
+use crate::results::EngineResult;
+use crate::retry::RetryPolicy;
+use crate::runner::ProcessRunner;
+use crate::runs::{FinishedHook, JobManager};
+use crate::segments::SegmentPolicy;
+use crate::topology::Topology;
+use crate::validation;
+use crate::workdir::WorkDir;
+
+#[derive(Parser)]
+#[command(name = "airsim", version, about = "Validate, run and export AirSim Studio models without the app")]
+struct Cli {
+    /// Also print informational backend messages to stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check models against the input schema
+    Validate {
+        #[arg(required = true)]
+        models: Vec<PathBuf>,
+    },
+    /// Validate models, then check references between their parts and
+    /// settings that are likely mistakes
+    Lint {
+        #[arg(required = true)]
+        models: Vec<PathBuf>,
+        /// Fail on warnings as well as errors
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Run a model and write the engine's output
+    Run {
+        model: PathBuf,
+        /// Engine output JSON [default: <model>.output.json]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Also export the result, in the format given by the extension
+        /// (.csv, .xlsx, .sqlite); may be repeated
+        #[arg(short, long, value_name = "FILE")]
+        export: Vec<PathBuf>,
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// Run every model in a folder: its `*.json` files and the `input.json`
+    /// of each subfolder
+    Batch {
+        dir: PathBuf,
+        /// Receives `<model>.json` outputs, their exports and `summary.csv`
+        #[arg(short, long)]
+        output_dir: PathBuf,
+        /// Models run at the same time
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+        /// Also export every result in this format; may be repeated
+        #[arg(short, long, value_enum)]
+        export: Vec<Format>,
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// Convert engine output JSON to CSV, XLSX or SQLite
+    Export {
+        /// Engine output JSON, as written by `run`
+        result: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        /// [default: from the output's extension]
+        #[arg(short, long, value_enum)]
+        format: Option<Format>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Csv,
+    Xlsx,
+    Sqlite,
+}
+
+impl From<Format> for ExportFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Csv => ExportFormat::Csv,
+            Format::Xlsx => ExportFormat::Xlsx,
+            Format::Sqlite => ExportFormat::Sqlite,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Method {
+    /// Newton with trust-region step control
+    Tr,
+    /// Sub-relaxation
+    Sur,
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Engine executable [default: $AIRSIM_ENGINE_PATH, then contam_engine
+    /// next to this program or on PATH]
+    #[arg(long, value_name = "PATH")]
+    engine: Option<String>,
+    /// Airflow solver
+    #[arg(short, long, value_enum, default_value_t = Method::Tr)]
+    method: Method,
+    /// Solve again with the other method when the solve does not converge
+    #[arg(long)]
+    retry: bool,
+    /// With --retry, also give a transient model's retry more airflow
+    /// iterations and stronger under-relaxation
+    #[arg(long, requires = "retry")]
+    relax_transient: bool,
+    /// Kill the engine after this long
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+    /// Kill the engine above this much memory (Linux only)
+    #[arg(long, value_name = "MB")]
+    memory_limit: Option<u64>,
+    /// Run transient windows in segments of about this length, checkpointed
+    /// as they finish
+    #[arg(long, value_name = "SECONDS")]
+    segment_length: Option<f64>,
+    /// Keep run files and segment checkpoints here, so that running an
+    /// interrupted model again resumes it [default: a temporary directory
+    /// removed on exit]
+    #[arg(long, value_name = "DIR")]
+    work_dir: Option<PathBuf>,
+}
+
+/// How a model or command fared, from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    Ok,
+    /// Not converged, or stopped before the end time
+    Partial,
+    Failed,
+}
+
+impl Status {
+    fn code(self) -> u8 {
+        match self {
+            Status::Ok => 0,
+            Status::Failed => 1,
+            Status::Partial => 2,
+        }
+    }
+
+    fn of(outcome: &RunOutcome) -> Self {
+        match outcome {
+            RunOutcome::Completed { partial: false, .. } => Status::Ok,
+            RunOutcome::Completed { partial: true, .. } => Status::Partial,
+            RunOutcome::Failed { error: EngineError::NotConverged { .. } } => Status::Partial,
+            RunOutcome::Failed { .. } | RunOutcome::Cancelled => Status::Failed,
+        }
+    }
+}
+
+/// Entry point of the `airsim` binary.
+pub fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_logging(cli.verbose);
+    let status = match cli.command {
+        Command::Validate { models } => Ok(validate(&models)),
+        Command::Lint { models, deny_warnings } => Ok(lint(&models, deny_warnings)),
+        Command::Run { model, output, export, run } => run_model(&model, output, &export, &run),
+        Command::Batch { dir, output_dir, jobs, export, run } => batch(&dir, &output_dir, jobs, &export, &run),
+        Command::Export { result, output, format } => export_result(&result, &output, format),
+    };
+    let status = status.unwrap_or_else(|message| {
+        eprintln!("airsim: {}", message);
+        Status::Failed
+    });
+    ExitCode::from(status.code())
+}
+
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        eprintln!("[{}] {}", record.level(), record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+fn init_logging(verbose: bool) {
+    static LOGGER: StderrLogger = StderrLogger;
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(if verbose { log::LevelFilter::Info } else { log::LevelFilter::Warn });
+    }
+}
+
+fn read_model(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// The model at `path` if it passes schema validation; otherwise prints why.
+fn checked_model(path: &Path) -> Option<String> {
+    let input = read_model(path).map_err(|message| println!("{}", message)).ok()?;
+    match validation::validate_input(&input) {
+        Ok(()) => Some(input),
+        Err(violations) => {
+            for violation in violations {
+                println!("{}: {}", path.display(), violation);
+            }
+            None
+        }
+    }
+}
+
+fn validate(models: &[PathBuf]) -> Status {
+    let mut status = Status::Ok;
+    for path in models {
+        match checked_model(path) {
+            Some(_) => println!("{}: ok", path.display()),
+            None => status = Status::Failed,
+        }
+    }
+    status
+}
+
+fn lint(models: &[PathBuf], deny_warnings: bool) -> Status {
+    let mut status = Status::Ok;
+    for path in models {
+        let model = checked_model(path).and_then(|input| serde_json::from_str::<Topology>(&input).ok());
+        let Some(model) = model else {
+            status = Status::Failed;
+            continue;
+        };
+        let findings = lint::lint(&model);
+        if findings.is_empty() {
+            println!("{}: ok", path.display());
+        }
+        for finding in findings {
+            println!("{}: {}", path.display(), finding);
+            if finding.severity == Severity::Error || deny_warnings {
+                status = Status::Failed;
+            }
+        }
+    }
+    status
+}
+
+/// Everything runs need: the engine, and a launcher with its own queue and
+/// work directory.
+struct Session {
+    launcher: Launcher,
+    runner: Arc<ProcessRunner>,
+    /// Removed on drop unless `--work-dir` was given
+    scratch: Option<PathBuf>,
+}
+
+impl Session {
+    fn open(args: &RunArgs, jobs: usize, on_finished: FinishedHook, on_progress: ProgressHook) -> Result<Self, String> {
+        let location = match &args.engine {
+            Some(path) => EnginePath { path: path.clone(), source: EngineSource::Settings },
+            None => discovery::find_engine_path(None),
+        };
+        let info = discovery::probe(location.clone(), location.command());
+        if !info.found {
+            return Err(format!("Engine not found at {}: {}", location.path, info.error.unwrap_or_default()));
+        }
+        if let Some(error) = info.error.as_deref().filter(|_| !info.compatible) {
+            log::warn!("{}", error);
+        }
+
+        let scratch = match &args.work_dir {
+            Some(_) => None,
+            None => Some(std::env::temp_dir().join(format!("airsim-{}", uuid::Uuid::new_v4()))),
+        };
+        let dir = args.work_dir.clone().or_else(|| scratch.clone()).unwrap_or_default();
+        let unusable = |e: std::io::Error| format!("Failed to create work directory {}: {}", dir.display(), e);
+        let launcher = Launcher {
+            jobs: JobManager::new(jobs.max(1), on_finished),
+            workdir: WorkDir::create(dir.join("runs")).map_err(unusable)?,
+            history: HistoryStore::create(dir.join("history")).map_err(unusable)?,
+            on_progress,
+            on_segment: Arc::new(|_, _| {}),
+        };
+        let runner = Arc::new(ProcessRunner::new(info, move || location.command()));
+        Ok(Self { launcher, runner, scratch })
+    }
+
+    fn launch(&self, input: String, args: &RunArgs) -> Result<String, String> {
+        let method = match args.method {
+            Method::Tr => SolverMethod::TrustRegion,
+            Method::Sur => SolverMethod::SubRelaxation,
+        };
+        let request = RunRequest {
+            input,
+            options: RunOptions { method, hdf5_output: None, verbosity: Verbosity::Verbose },
+            limits: RunLimits { timeout_secs: args.timeout, memory_limit_mb: args.memory_limit },
+            // Nothing is recorded, so nothing is served from the history; not
+            // fresh, so checkpoints in `--work-dir` are resumed
+            fresh: false,
+            history_quota: None,
+            retry: args.retry.then_some(RetryPolicy { relax_transient: args.relax_transient }),
+            warm_start: false,
+            segments: args.segment_length.map(|length| SegmentPolicy { length }),
+        };
+        self.launcher.launch(self.runner.clone(), request)
+    }
+
+    /// Wall-clock time the run spent running
+    fn duration(&self, run_id: &str) -> Option<Duration> {
+        let status = self.launcher.jobs.status(run_id)?;
+        let started = status.started_at.unwrap_or(status.queued_at);
+        Some(Duration::from_millis(status.finished_at?.saturating_sub(started)))
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.scratch {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn run_model(model: &Path, output: Option<PathBuf>, exports: &[PathBuf], args: &RunArgs) -> Result<Status, String> {
+    let formats = exports
+        .iter()
+        .map(|path| ExportFormat::from_path(path).ok_or_else(|| format!("Unknown export format: {}", path.display())))
+        .collect::<Result<Vec<_>, _>>()?;
+    let input = read_model(model)?;
+
+    // Progress on one line, only for a person watching
+    let shown = Arc::new(AtomicBool::new(false));
+    let on_progress: ProgressHook = if std::io::stderr().is_terminal() {
+        let shown = shown.clone();
+        Arc::new(move |_, progress: Progress| {
+            shown.store(true, Ordering::Relaxed);
+            eprint!("\r{:5.1}%  t = {:.0} / {:.0} s ", progress.percent, progress.time, progress.end_time);
+        })
+    } else {
+        Arc::new(|_, _| {})
+    };
+    let session = Session::open(args, 1, Box::new(|_, _| {}), on_progress)?;
+    let run_id = session.launch(input, args)?;
+    let outcome = session.launcher.jobs.wait(&run_id, None)?.ok_or("Run was dropped from the queue")?;
+    if shown.load(Ordering::Relaxed) {
+        eprintln!();
+    }
+
+    let name = model_name(model);
+    println!("{}", describe(&name, &outcome, session.duration(&run_id)));
+    if let RunOutcome::Completed { result, .. } = &outcome {
+        let output = output.unwrap_or_else(|| model.with_extension("output.json"));
+        write_output(result, &output)?;
+        for (path, format) in exports.iter().zip(formats) {
+            export::export(result, format, path)?;
+        }
+    }
+    Ok(Status::of(&outcome))
+}
+
+/// A line for the run log: how the run ended and its solver statistics.
+fn describe(name: &str, outcome: &RunOutcome, duration: Option<Duration>) -> String {
+    let took = duration.map(|d| format!(" in {:.1} s", d.as_secs_f64())).unwrap_or_default();
+    match outcome {
+        RunOutcome::Completed { partial, summary, .. } => {
+            let mut stats = Vec::new();
+            stats.extend(summary.iterations.map(|n| format!("{} iterations", n)));
+            stats.extend(summary.max_residual.map(|r| format!("max residual {:.2e} kg/s", r)));
+            stats.extend(summary.output_steps.map(|n| format!("{} output steps", n)));
+            let state = if *partial { "partial" } else { "completed" };
+            let stats = if stats.is_empty() { String::new() } else { format!(" ({})", stats.join(", ")) };
+            format!("{}: {}{}{}", name, state, took, stats)
+        }
+        RunOutcome::Failed { error } => format!("{}: failed{}: {}", name, took, error),
+        RunOutcome::Cancelled => format!("{}: cancelled", name),
+    }
+}
+
+/// The engine's output layout, so results can be read back by `export`.
+fn write_output(result: &EngineResult, path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, result)
+        .map_err(|e| e.to_string())
+        .and_then(|()| writer.flush().map_err(|e| e.to_string()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// `case01` for `case01.json` and `case01/input.json`.
+fn model_name(path: &Path) -> String {
+    let named = match path.file_name() {
+        Some(name) if name == "input.json" => path.parent().and_then(Path::file_name),
+        _ => path.file_stem(),
+    };
+    named.map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+/// Models of a batch by name: `<name>.json` files, and `<name>/input.json`
+/// as in `validation/`.
+fn find_models(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut models: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.is_dir() {
+                let input = path.join("input.json");
+                input.is_file().then(|| (model_name(&input), input))
+            } else {
+                let json = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"));
+                json.then(|| (model_name(&path), path))
+            }
+        })
+        .collect();
+    models.sort();
+    Ok(models)
+}
+
+/// One row of `summary.csv`.
+struct BatchRow {
+    name: String,
+    status: Status,
+    duration: Option<Duration>,
+    outcome: Option<RunOutcome>,
+    error: Option<String>,
+}
+
+fn batch(dir: &Path, output_dir: &Path, jobs: usize, formats: &[Format], args: &RunArgs) -> Result<Status, String> {
+    let models = find_models(dir)?;
+    if models.is_empty() {
+        return Err(format!("No models in {}", dir.display()));
+    }
+    fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create {}: {}", output_dir.display(), e))?;
+
+    let (finished, receive) = mpsc::channel();
+    let on_finished = Box::new(move |run_id: &str, outcome: &RunOutcome| {
+        let _ = finished.send((run_id.to_string(), outcome.clone()));
+    });
+    let session = Session::open(args, jobs, on_finished, Arc::new(|_, _| {}))?;
+
+    // Keep only `jobs` runs in flight, so every finished run can still be
+    // looked up when its outcome arrives
+    let mut pending: VecDeque<(String, PathBuf)> = models.into();
+    let mut running: HashMap<String, String> = HashMap::new();
+    let mut rows = Vec::new();
+    loop {
+        while running.len() < jobs.max(1) {
+            let Some((name, path)) = pending.pop_front() else { break };
+            match read_model(&path).and_then(|input| session.launch(input, args)) {
+                Ok(run_id) => {
+                    running.insert(run_id, name);
+                }
+                Err(error) => {
+                    println!("{}: failed: {}", name, error);
+                    let status = Status::Failed;
+                    rows.push(BatchRow { name, status, duration: None, outcome: None, error: Some(error) });
+                }
+            }
+        }
+        if running.is_empty() {
+            break;
+        }
+        let (run_id, outcome) = receive.recv().map_err(|_| "Run queue stopped")?;
+        let Some(name) = running.remove(&run_id) else { continue };
+        let duration = session.duration(&run_id);
+        println!("{}", describe(&name, &outcome, duration));
+        let error = save_batch_output(&name, &outcome, output_dir, formats).err();
+        if let Some(error) = &error {
+            eprintln!("{}: {}", name, error);
+        }
+        let status = if error.is_some() { Status::Failed } else { Status::of(&outcome) };
+        rows.push(BatchRow { name, status, duration, outcome: Some(outcome), error });
+    }
+
+    rows.sort_by(|a, b| a.name.cmp(&b.name));
+    write_summary(&rows, &output_dir.join("summary.csv"))?;
+    let failed = rows.iter().filter(|row| row.status != Status::Ok).count();
+    println!("{} models, {} not ok", rows.len(), failed);
+    Ok(rows.iter().map(|row| row.status).max().unwrap_or(Status::Ok))
+}
+
+fn save_batch_output(name: &str, outcome: &RunOutcome, dir: &Path, formats: &[Format]) -> Result<(), String> {
+    let RunOutcome::Completed { result, .. } = outcome else { return Ok(()) };
+    write_output(result, &dir.join(format!("{}.json", name)))?;
+    for &format in formats {
+        let format = ExportFormat::from(format);
+        export::export(result, format, &dir.join(format!("{}.{}", name, format.extension())))?;
+    }
+    Ok(())
+}
+
+fn write_summary(rows: &[BatchRow], path: &Path) -> Result<(), String> {
+    let mut out = String::from("model,status,duration_s,iterations,max_residual_kg_s,output_steps,error\n");
+    for row in rows {
+        let summary = match &row.outcome {
+            Some(RunOutcome::Completed { summary, .. }) => Some(summary),
+            _ => None,
+        };
+        let error = match (&row.error, &row.outcome) {
+            (Some(error), _) => error.clone(),
+            (None, Some(RunOutcome::Failed { error })) => error.to_string(),
+            (None, Some(RunOutcome::Cancelled)) => "cancelled".to_string(),
+            _ => String::new(),
+        };
+        let status = match row.status {
+            Status::Ok => "ok",
+            Status::Partial => "partial",
+            Status::Failed => "failed",
+        };
+        let cells = [
+            export::csv_field(&row.name),
+            status.to_string(),
+            row.duration.map(|d| format!("{:.3}", d.as_secs_f64())).unwrap_or_default(),
+            summary.and_then(|s| s.iterations).map(|n| n.to_string()).unwrap_or_default(),
+            summary.and_then(|s| s.max_residual).map(|r| format!("{:e}", r)).unwrap_or_default(),
+            summary.and_then(|s| s.output_steps).map(|n| n.to_string()).unwrap_or_default(),
+            export::csv_field(&error),
+        ];
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    fs::write(path, out).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn export_result(result: &Path, output: &Path, format: Option<Format>) -> Result<Status, String> {
+    let format = match format {
+        Some(format) => ExportFormat::from(format),
+        None => ExportFormat::from_path(output)
+            .ok_or_else(|| format!("Cannot tell the format of {}; pass --format", output.display()))?,
+    };
+    let file = File::open(result).map_err(|e| format!("Failed to read {}: {}", result.display(), e))?;
+    // Large series spill next to the output while it is written
+    let spill_dir = output.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let result = EngineResult::from_reader(BufReader::new(file), Some(spill_dir))
+        .map_err(|e| format!("Failed to parse {}: {}", result.display(), e))?;
+    export::export(&result, format, output)?;
+    Ok(Status::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::error::EngineLogs;
+    use crate::runner::MockRunner;
+    use crate::summary::RunSummary;
+    use crate::testing::TestDir;
+
+    fn completed(partial: bool) -> RunOutcome {
+        let file = File::open(MockRunner::case_dir("case01_3room").join("output.json")).unwrap();
+        let result = EngineResult::from_reader(file, None).unwrap();
+        let summary = RunSummary { iterations: Some(7), max_residual: Some(2.5e-6), ..RunSummary::default() };
+        RunOutcome::Completed {
+            result: Arc::new(result),
+            partial,
+            max_residual: None,
+            summary,
+            logs: EngineLogs::default(),
+            cached_run_id: None,
+            attempts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn exit_codes_follow_the_engine() {
+        let failed = |error| RunOutcome::Failed { error };
+        assert_eq!(Status::of(&completed(false)).code(), 0);
+        assert_eq!(Status::of(&completed(true)).code(), 2);
+        assert_eq!(Status::of(&failed(EngineError::NotConverged { logs: EngineLogs::default() })).code(), 2);
+        assert_eq!(Status::of(&failed(EngineError::InputError { logs: EngineLogs::default() })).code(), 1);
+        assert_eq!(Status::of(&failed(EngineError::io("disk full".to_string()))).code(), 1);
+        assert_eq!(Status::of(&RunOutcome::Cancelled).code(), 1);
+        // A batch exits with its worst model, failures over partial results
+        assert_eq!([Status::Ok, Status::Failed, Status::Partial].into_iter().max(), Some(Status::Failed));
+    }
+
+    #[test]
+    fn models_are_named_after_their_file_or_folder() {
+        assert_eq!(model_name(Path::new("models/office.json")), "office");
+        assert_eq!(model_name(Path::new("validation/case01_3room/input.json")), "case01_3room");
+        assert_eq!(model_name(Path::new("input.json")), "input.json");
+    }
+
+    #[test]
+    fn batches_find_json_files_and_input_folders() {
+        let dir = TestDir::new();
+        fs::write(dir.0.join("b.json"), "{}").unwrap();
+        fs::write(dir.0.join("notes.txt"), "").unwrap();
+        fs::create_dir(dir.0.join("a")).unwrap();
+        fs::write(dir.0.join("a").join("input.json"), "{}").unwrap();
+        fs::create_dir(dir.0.join("empty")).unwrap();
+
+        let models = find_models(&dir.0).unwrap();
+        let names: Vec<&str> = models.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(models[0].1, dir.0.join("a").join("input.json"));
+        assert!(find_models(&dir.0.join("missing")).is_err());
+    }
+
+    #[test]
+    fn summaries_list_every_model() {
+        let dir = TestDir::new();
+        let row = |name: &str, outcome: RunOutcome, error: Option<&str>| BatchRow {
+            name: name.to_string(),
+            status: if error.is_some() { Status::Failed } else { Status::of(&outcome) },
+            duration: Some(Duration::from_millis(1500)),
+            outcome: Some(outcome),
+            error: error.map(str::to_string),
+        };
+        let rows = [
+            row("office, 2F", completed(false), None),
+            row("lobby", completed(true), None),
+            row("attic", RunOutcome::Failed { error: EngineError::InputError { logs: EngineLogs::default() } }, None),
+            row("garage", completed(false), Some("Failed to write \"garage.csv\"")),
+        ];
+        let path = dir.0.join("summary.csv");
+        write_summary(&rows, &path).unwrap();
+
+        let summary = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "model,status,duration_s,iterations,max_residual_kg_s,output_steps,error");
+        assert_eq!(lines[1], "\"office, 2F\",ok,1.500,7,2.5e-6,,");
+        assert_eq!(lines[2], "lobby,partial,1.500,7,2.5e-6,,");
+        assert!(lines[3].starts_with("attic,failed,1.500,,,,"), "{}", lines[3]);
+        assert_eq!(lines[4], "garage,failed,1.500,7,2.5e-6,,\"Failed to write \"\"garage.csv\"\"\"");
+    }
+}
@@ -0,0 +1,129 @@
+//! Flat `f64` arrays for the series of transient results.
+//!
+//! An array that grows past `MAP_THRESHOLD_BYTES` while it is being filled
+//! moves to a private file in the work directory and is memory-mapped once
+//! complete, so an annual result does not have to fit in RAM. The file is
+//! removed with the array.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::mem::ManuallyDrop;
+use std::path::{Path, PathBuf};
+
+use memmap2::Mmap;
+
+use crate::workdir;
+
+/// Arrays larger than this are kept in a mapped file.
+pub const MAP_THRESHOLD_BYTES: usize = 64 * 1024 * 1024;
+
+const VALUE_BYTES: usize = std::mem::size_of::<f64>();
+
+pub enum Column {
+    Memory(Vec<f64>),
+    Mapped(MappedColumn),
+}
+
+impl Column {
+    pub fn get(&self, index: usize) -> Option<f64> {
+        match self {
+            Column::Memory(values) => values.get(index).copied(),
+            Column::Mapped(mapped) => {
+                let bytes = mapped.map.get(index * VALUE_BYTES..(index + 1) * VALUE_BYTES)?;
+                Some(f64::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Column::Memory(values) => write!(f, "Column::Memory({} values)", values.len()),
+            Column::Mapped(mapped) => write!(f, "Column::Mapped({} values at {})", mapped.len, mapped.path.display()),
+        }
+    }
+}
+
+/// Little-endian values in a file only this process writes.
+pub struct MappedColumn {
+    map: ManuallyDrop<Mmap>,
+    path: PathBuf,
+    len: usize,
+}
+
+impl Drop for MappedColumn {
+    fn drop(&mut self) {
+        // Unmap first: Windows refuses to delete a mapped file.
+        // SAFETY: `map` is not used after this.
+        unsafe { ManuallyDrop::drop(&mut self.map) };
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Appends values to a `Column`, moving them to a file past the threshold.
+pub struct ColumnBuilder {
+    values: Vec<f64>,
+    /// Where to spill to; `None` keeps everything in memory
+    spill_dir: Option<PathBuf>,
+    spill: Option<(BufWriter<File>, PathBuf)>,
+    len: usize,
+}
+
+impl ColumnBuilder {
+    pub fn new(spill_dir: Option<&Path>) -> Self {
+        Self { values: Vec::new(), spill_dir: spill_dir.map(Path::to_path_buf), spill: None, len: 0 }
+    }
+
+    pub fn push(&mut self, value: f64) -> io::Result<()> {
+        self.len += 1;
+        if let Some((file, _)) = &mut self.spill {
+            return file.write_all(&value.to_le_bytes());
+        }
+        self.values.push(value);
+        if self.values.len() * VALUE_BYTES >= MAP_THRESHOLD_BYTES {
+            self.start_spill()?;
+        }
+        Ok(())
+    }
+
+    fn start_spill(&mut self) -> io::Result<()> {
+        let Some(dir) = &self.spill_dir else { return Ok(()) };
+        let path = workdir::spill_file(dir);
+        let file = workdir::create_private(&path)?;
+        // Track the file before writing so a failure below still removes it
+        let (file, _) = self.spill.insert((BufWriter::new(file), path));
+        for value in std::mem::take(&mut self.values) {
+            file.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<Column> {
+        let Some((file, path)) = self.spill.take() else {
+            return Ok(Column::Memory(std::mem::take(&mut self.values)));
+        };
+        let mapped = file.into_inner().map_err(|e| e.into_error()).and_then(|file| {
+            // SAFETY: the file is private to this process and never written again
+            unsafe { Mmap::map(&file) }
+        });
+        match mapped {
+            Ok(map) => Ok(Column::Mapped(MappedColumn { map: ManuallyDrop::new(map), path, len: self.len })),
+            Err(e) => {
+                let _ = std::fs::remove_file(&path);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Drop for ColumnBuilder {
+    fn drop(&mut self) {
+        // Abandoned halfway, e.g. by a parse error
+        if let Some((file, path)) = self.spill.take() {
+            drop(file);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
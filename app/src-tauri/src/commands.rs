@@ -0,0 +1,277 @@
+//! What the app's Tauri commands do, on the backend's state rather than an
+//! `AppHandle`. `gui` wraps these in commands and events, so they can be
+//! exercised against the mock and process runners without a webview.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::engine::RunOutcome;
+use crate::export::{self, ExportFormat};
+use crate::history::HistoryStore;
+use crate::launch::RunRequest;
+use crate::limits::RunLimits;
+use crate::options::RunOptions;
+use crate::result_store::ResultStore;
+use crate::results::{EngineResult, SliceQuery};
+use crate::retry::RetryPolicy;
+use crate::runs::{JobManager, RunStatus};
+use crate::segments::SegmentPolicy;
+use crate::settings::Settings;
+use crate::workdir::WorkDir;
+
+/// Payload of `engine-run-finished` and `wait_run`.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunFinished {
+    pub run_id: String,
+    #[serde(flatten)]
+    pub outcome: RunOutcome,
+}
+
+/// The arguments of `run_engine`; everything but the input is optional.
+#[derive(Debug, Clone, Default)]
+pub struct RunArgs {
+    pub input: String,
+    pub options: Option<RunOptions>,
+    pub limits: Option<RunLimits>,
+    pub fresh: Option<bool>,
+    pub retry: Option<RetryPolicy>,
+    pub warm_start: Option<bool>,
+    pub segments: Option<SegmentPolicy>,
+}
+
+impl RunArgs {
+    /// The run to launch, with limits and the history quota the command
+    /// leaves out taken from the user's settings.
+    pub fn into_request(self, settings: &Settings) -> RunRequest {
+        RunRequest {
+            input: self.input,
+            options: self.options.unwrap_or_default(),
+            limits: self.limits.unwrap_or_default().or(settings.run_limits()),
+            fresh: self.fresh.unwrap_or(false),
+            history_quota: (settings.history_quota_mb > 0).then(|| settings.history_quota_bytes()),
+            retry: self.retry,
+            warm_start: self.warm_start.unwrap_or(false),
+            segments: self.segments,
+        }
+    }
+}
+
+/// Keep a completed run's result for `find_result`; called as runs finish.
+pub fn store_result(results: &ResultStore, run_id: &str, outcome: &RunOutcome) {
+    if let RunOutcome::Completed { result, .. } = outcome {
+        results.insert(run_id, result.clone());
+    }
+}
+
+/// A run's result from memory, or from the history if it has been evicted.
+pub fn find_result(
+    results: &ResultStore,
+    history: &HistoryStore,
+    workdir: &WorkDir,
+    run_id: &str,
+) -> Result<Arc<EngineResult>, String> {
+    if let Some(result) = results.get(run_id) {
+        return Ok(result);
+    }
+    let result = Arc::new(history.load_result(run_id, workdir.path())?);
+    results.insert(run_id, result.clone());
+    Ok(result)
+}
+
+/// Part of a transient result as little-endian f64s, laid out as described
+/// on `SliceQuery`.
+pub fn result_slice(result: &EngineResult, query: &SliceQuery) -> Result<Vec<u8>, String> {
+    let EngineResult::Transient(transient) = result else {
+        return Err("Steady results have no time series".to_string());
+    };
+    Ok(transient.slice(query)?.iter().flat_map(|v| v.to_le_bytes()).collect())
+}
+
+/// Write a result to `path` as CSV, XLSX or SQLite, chosen by the extension.
+pub fn export_result(result: &EngineResult, path: &Path) -> Result<(), String> {
+    let format =
+        ExportFormat::from_path(path).ok_or_else(|| format!("Unsupported export format: {}", path.display()))?;
+    export::export(result, format, path)
+}
+
+pub fn cancel_run(jobs: &JobManager, run_id: &str) -> Result<(), String> {
+    if jobs.cancel(run_id) {
+        Ok(())
+    } else {
+        Err(format!("No active run with ID {}", run_id))
+    }
+}
+
+pub fn run_status(jobs: &JobManager, run_id: &str) -> Result<RunStatus, String> {
+    jobs.status(run_id).ok_or_else(|| format!("No run with ID {}", run_id))
+}
+
+/// Block until the run finishes; `None` if `timeout_ms` elapses first.
+pub fn wait_run(jobs: &JobManager, run_id: String, timeout_ms: Option<u64>) -> Result<Option<RunFinished>, String> {
+    let outcome = jobs.wait(&run_id, timeout_ms.map(Duration::from_millis))?;
+    Ok(outcome.map(|outcome| RunFinished { run_id, outcome }))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::launch::Launcher;
+    use crate::result_store::ResultOverview;
+    use crate::runner::{EngineRunner, MockRunner};
+    use crate::runs::RunState;
+    use crate::testing::{case_input, case_output, TestDir};
+
+    /// The state `gui::run` manages, wired the same way.
+    struct App {
+        launcher: Launcher,
+        results: Arc<ResultStore>,
+        settings: Settings,
+    }
+
+    impl App {
+        fn new(dir: &TestDir, settings: Settings) -> Self {
+            let results = Arc::new(ResultStore::default());
+            let finished = results.clone();
+            let launcher = Launcher {
+                jobs: JobManager::new(
+                    settings.max_concurrent_runs,
+                    Box::new(move |run_id, outcome| store_result(&finished, run_id, outcome)),
+                ),
+                workdir: WorkDir::create(dir.0.join("runs")).unwrap(),
+                history: HistoryStore::create(dir.0.join("history")).unwrap(),
+                on_progress: Arc::new(|_, _| {}),
+                on_segment: Arc::new(|_, _| {}),
+            };
+            Self { launcher, results, settings }
+        }
+
+        /// `run_engine` followed by `wait_run`, serialized as the webview sees it.
+        fn run(&self, runner: impl EngineRunner + 'static, args: RunArgs) -> (String, Value) {
+            let request = args.into_request(&self.settings);
+            let run_id = self.launcher.launch(Arc::new(runner), request).unwrap();
+            let finished = wait_run(&self.launcher.jobs, run_id.clone(), Some(30_000)).unwrap().expect("run finished");
+            (run_id, serde_json::to_value(finished).unwrap())
+        }
+
+        fn find_result(&self, run_id: &str) -> Result<Arc<EngineResult>, String> {
+            find_result(&self.results, &self.launcher.history, &self.launcher.workdir, run_id)
+        }
+    }
+
+    fn args(case: &str) -> RunArgs {
+        RunArgs { input: case_input(case), ..RunArgs::default() }
+    }
+
+    fn decode(bytes: &[u8]) -> Vec<f64> {
+        bytes.chunks(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect()
+    }
+
+    #[test]
+    fn a_run_is_fetched_sliced_and_exported() {
+        let dir = TestDir::new();
+        let app = App::new(&dir, Settings::default());
+        let (run_id, finished) = app.run(MockRunner::case("case02_co2_source"), args("case02_co2_source"));
+        assert_eq!(finished["runId"], run_id.as_str());
+        assert_eq!(finished["status"], "completed");
+        assert_eq!(run_status(&app.launcher.jobs, &run_id).unwrap().state, RunState::Completed);
+
+        let result = app.find_result(&run_id).unwrap();
+        let ResultOverview::Transient(overview) = ResultOverview::from(&*result) else { panic!("steady overview") };
+        let steps = case_output("case02_co2_source")["timeSeries"].as_array().unwrap().len();
+        assert_eq!(overview.total_steps, steps);
+        let times = decode(&result_slice(&result, &SliceQuery::default()).unwrap());
+        assert_eq!(times.len(), steps);
+
+        // Evicted results come back from the history
+        let reloaded = App { results: Arc::new(ResultStore::default()), ..app };
+        let result = reloaded.find_result(&run_id).unwrap();
+        assert_eq!(decode(&result_slice(&result, &SliceQuery::default()).unwrap()), times);
+        assert!(reloaded.find_result(&uuid::Uuid::new_v4().to_string()).is_err());
+
+        let path = dir.0.join("export.csv");
+        export_result(&result, &path).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        assert!(export_result(&result, &dir.0.join("export.txt")).unwrap_err().contains("Unsupported"));
+    }
+
+    #[test]
+    fn steady_results_have_no_slices() {
+        let dir = TestDir::new();
+        let app = App::new(&dir, Settings::default());
+        let (run_id, _) = app.run(MockRunner::case("case01_3room"), args("case01_3room"));
+        let result = app.find_result(&run_id).unwrap();
+        assert!(matches!(ResultOverview::from(&*result), ResultOverview::Steady(_)));
+        assert!(result_slice(&result, &SliceQuery::default()).is_err());
+    }
+
+    #[test]
+    fn settings_fill_in_what_the_command_leaves_out() {
+        let dir = TestDir::new();
+        let settings = Settings { run_timeout_secs: 1, history_quota_mb: 0, ..Settings::default() };
+        let request = args("case01_3room").into_request(&settings);
+        assert_eq!(request.limits.timeout_secs, Some(1));
+        assert_eq!(request.history_quota, None, "a zero quota keeps no history");
+
+        let app = App::new(&dir, settings);
+        let slow = || MockRunner { duration: Duration::from_secs(10), ..MockRunner::case("case01_3room") };
+        let (_, finished) = app.run(slow(), args("case01_3room"));
+        assert_eq!((&finished["status"], &finished["error"]["kind"]), (&Value::from("failed"), &"timedOut".into()));
+        assert!(app.launcher.history.list().is_empty());
+
+        // Limits given with the run win over the settings
+        let limits = RunLimits { timeout_secs: Some(2), ..RunLimits::default() };
+        let (_, finished) = app.run(slow(), RunArgs { limits: Some(limits), ..args("case01_3room") });
+        assert_eq!(finished["error"]["timeoutSecs"], 2);
+    }
+
+    #[test]
+    fn unknown_and_finished_runs_are_errors() {
+        let dir = TestDir::new();
+        let app = App::new(&dir, Settings::default());
+        let jobs = &app.launcher.jobs;
+        assert!(cancel_run(jobs, "unknown").is_err());
+        assert!(run_status(jobs, "unknown").is_err());
+        assert!(wait_run(jobs, "unknown".to_string(), Some(0)).is_err());
+
+        let (run_id, _) = app.run(MockRunner::case("case01_3room"), args("case01_3room"));
+        assert!(cancel_run(jobs, &run_id).unwrap_err().contains("No active run"));
+    }
+
+    #[test]
+    fn cancelling_a_running_engine() {
+        let dir = TestDir::new();
+        let app = App::new(&dir, Settings::default());
+        let slow = MockRunner { duration: Duration::from_secs(10), ..MockRunner::case("case01_3room") };
+        let request = args("case01_3room").into_request(&app.settings);
+        let run_id = app.launcher.launch(Arc::new(slow), request).unwrap();
+        assert!(wait_run(&app.launcher.jobs, run_id.clone(), Some(50)).unwrap().is_none());
+
+        cancel_run(&app.launcher.jobs, &run_id).unwrap();
+        let finished = wait_run(&app.launcher.jobs, run_id.clone(), Some(30_000)).unwrap().unwrap();
+        assert!(matches!(finished.outcome, RunOutcome::Cancelled));
+        assert!(app.find_result(&run_id).is_err(), "cancelled runs have no result");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_drive_the_process_engine() {
+        use crate::testing::{copy_case_output, fake_engine};
+
+        let dir = TestDir::new();
+        let app = App::new(&dir, Settings::default());
+        let engine = fake_engine(&dir.0, &copy_case_output("case02_co2_source"));
+        let (run_id, _) = app.run(engine, args("case02_co2_source"));
+        let result = app.find_result(&run_id).unwrap();
+        assert!(!result_slice(&result, &SliceQuery::default()).unwrap().is_empty());
+
+        let (_, finished) = app.run(fake_engine(&dir.0, "echo 'Error: bad input' >&2\nexit 1\n"), args("case01_3room"));
+        assert_eq!(finished["error"]["kind"], "inputError");
+        assert!(finished["error"]["logs"]["stderr"].as_str().unwrap().contains("bad input"));
+    }
+}
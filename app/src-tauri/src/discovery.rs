@@ -0,0 +1,300 @@
+//! Locating the `contam_engine` executable and probing what it supports.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+#[cfg(feature = "gui")]
+use std::sync::Mutex;
+#[cfg(feature = "gui")]
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+/// `externalBin` name in tauri.conf.json.
+pub const SIDECAR_NAME: &str = "contam_engine";
+
+/// Environment variable that overrides every other engine location.
+pub const ENGINE_PATH_ENV: &str = "AIRSIM_ENGINE_PATH";
+
+/// Oldest engine whose CLI and output format this backend understands.
+const MIN_ENGINE_VERSION: (u32, u32, u32) = (0, 2, 0);
+
+/// Prefix of the first line printed by `contam_engine -h`.
+const BANNER_PREFIX: &str = "AirSim Studio Engine v";
+
+/// Where the engine path came from, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EngineSource {
+    Env,
+    Settings,
+    /// The `externalBin` sidecar shipped with the app
+    Bundled,
+    Path,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnginePath {
+    pub path: String,
+    pub source: EngineSource,
+}
+
+/// Resolve the engine: `AIRSIM_ENGINE_PATH`, then the configured path, then
+/// the bundled sidecar, then `contam_engine` on PATH.
+pub fn find_engine_path(configured: Option<&str>) -> EnginePath {
+    resolve_engine_path(std::env::var_os(ENGINE_PATH_ENV), configured, &sidecar_dirs())
+}
+
+/// Where a bundled sidecar may be: `tauri build` installs it next to the app
+/// executable and `tauri dev` copies it into the target dir. In debug builds
+/// also the externalBin source in src-tauri/ itself.
+fn sidecar_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            dirs.push(exe_dir.to_path_buf());
+            // Also check parent directory (for dev builds)
+            dirs.extend(exe_dir.parent().map(Path::to_path_buf));
+        }
+    }
+    if cfg!(debug_assertions) {
+        dirs.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+    }
+    dirs
+}
+
+/// `find_engine_path` with the environment variable and sidecar directories
+/// passed in.
+fn resolve_engine_path(env: Option<OsString>, configured: Option<&str>, dirs: &[PathBuf]) -> EnginePath {
+    if let Some(path) = env.filter(|p| !p.is_empty()) {
+        return EnginePath { path: path.to_string_lossy().to_string(), source: EngineSource::Env };
+    }
+    if let Some(path) = configured.filter(|p| !p.trim().is_empty()) {
+        return EnginePath { path: path.to_string(), source: EngineSource::Settings };
+    }
+
+    // The sidecar has the target triple stripped when installed; also accept
+    // the triple-suffixed name
+    let names = [exe_name(SIDECAR_NAME), exe_name(&format!("{}-{}", SIDECAR_NAME, env!("TARGET_TRIPLE")))];
+    for dir in dirs {
+        for name in &names {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                return EnginePath { path: candidate.to_string_lossy().to_string(), source: EngineSource::Bundled };
+            }
+        }
+    }
+
+    // Fallback: assume it's in PATH
+    EnginePath { path: SIDECAR_NAME.to_string(), source: EngineSource::Path }
+}
+
+impl EnginePath {
+    /// A plain command for this engine. The app routes bundled engines
+    /// through the shell plugin's sidecar resolution instead.
+    pub fn command(&self) -> Command {
+        Command::new(&self.path)
+    }
+}
+
+/// M-19: Cross-platform executable name
+fn exe_name(stem: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("{}.exe", stem)
+    } else {
+        stem.to_string()
+    }
+}
+
+/// What `contam_engine -h` reports about itself.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineInfo {
+    #[serde(flatten)]
+    pub location: EnginePath,
+    /// The engine could be executed
+    pub found: bool,
+    /// `X.Y.Z` from the help banner
+    pub version: Option<String>,
+    /// `--hdf5` support was compiled in
+    pub hdf5: bool,
+    /// Found, and at least `MIN_ENGINE_VERSION`
+    pub compatible: bool,
+    pub min_version: String,
+    /// Why the engine could not be probed, if it could not
+    pub error: Option<String>,
+}
+
+/// Run `<engine> -h` through `cmd` and parse its banner and option list.
+pub fn probe(location: EnginePath, mut cmd: Command) -> EngineInfo {
+    let min_version = format_version(MIN_ENGINE_VERSION);
+    let help = match cmd.arg("-h").output() {
+        Ok(out) => String::from_utf8_lossy(&out.stdout).into_owned(),
+        Err(e) => {
+            return EngineInfo {
+                location,
+                found: false,
+                version: None,
+                hdf5: false,
+                compatible: false,
+                min_version,
+                error: Some(e.to_string()),
+            };
+        }
+    };
+
+    let version = help
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(BANNER_PREFIX))
+        .map(|v| v.trim().to_string());
+    let compatible = version
+        .as_deref()
+        .and_then(parse_version)
+        .is_some_and(|v| v >= MIN_ENGINE_VERSION);
+    let error = match &version {
+        None => Some("Unrecognized engine: no version banner in -h output".to_string()),
+        Some(v) if !compatible => Some(format!("Engine v{} is older than the required v{}", v, min_version)),
+        Some(_) => None,
+    };
+
+    EngineInfo {
+        location,
+        found: true,
+        hdf5: help.contains("--hdf5"),
+        version,
+        compatible,
+        min_version,
+        error,
+    }
+}
+
+impl EngineInfo {
+    /// Whether the reported version is at least `min`; `false` if unknown.
+    pub fn at_least(&self, min: (u32, u32, u32)) -> bool {
+        self.version.as_deref().and_then(parse_version).is_some_and(|v| v >= min)
+    }
+}
+
+/// Remembers the last probe so per-run bookkeeping does not spawn `-h` every
+/// time. Re-probes when the path changes or the executable is replaced.
+#[cfg(feature = "gui")]
+#[derive(Default)]
+pub struct EngineInfoCache {
+    last: Mutex<Option<(Option<SystemTime>, EngineInfo)>>,
+}
+
+#[cfg(feature = "gui")]
+impl EngineInfoCache {
+    pub fn get_or_probe(&self, location: &EnginePath, cmd: impl FnOnce() -> Command) -> EngineInfo {
+        let modified = std::fs::metadata(&location.path).and_then(|m| m.modified()).ok();
+        let mut last = self.last.lock().unwrap();
+        if let Some((stamp, info)) = last.as_ref() {
+            if info.location.path == location.path && *stamp == modified && info.found {
+                return info.clone();
+            }
+        }
+        let info = probe(location.clone(), cmd());
+        *last = Some((modified, info.clone()));
+        info
+    }
+}
+
+fn parse_version(v: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = v.split('.').map(|p| p.parse::<u32>().ok());
+    Some((parts.next()??, parts.next()??, parts.next().flatten().unwrap_or(0)))
+}
+
+fn format_version((major, minor, patch): (u32, u32, u32)) -> String {
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestDir;
+
+    fn info(version: Option<&str>) -> EngineInfo {
+        EngineInfo {
+            location: EnginePath { path: SIDECAR_NAME.to_string(), source: EngineSource::Path },
+            found: true,
+            version: version.map(str::to_string),
+            hdf5: false,
+            compatible: false,
+            min_version: format_version(MIN_ENGINE_VERSION),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn parses_banner_versions() {
+        assert_eq!(parse_version("1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("0.10"), Some((0, 10, 0)));
+        assert_eq!(parse_version("0.2.0-beta"), Some((0, 2, 0)), "a pre-release suffix counts as the release");
+        assert_eq!(parse_version("1"), None);
+        assert_eq!(parse_version("v1.2.3"), None);
+        assert_eq!(format_version((0, 2, 0)), "0.2.0");
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(info(Some("1.10.0")).at_least((1, 9, 0)));
+        assert!(info(Some("0.2.0")).at_least(MIN_ENGINE_VERSION));
+        assert!(!info(Some("0.1.9")).at_least(MIN_ENGINE_VERSION));
+        assert!(!info(Some("unknown")).at_least((0, 0, 0)));
+        assert!(!info(None).at_least((0, 0, 0)));
+    }
+
+    #[cfg(unix)]
+    fn probe_help(help: &str) -> EngineInfo {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(format!("printf '%s\\n' '{}'", help));
+        probe(EnginePath { path: "sh".to_string(), source: EngineSource::Path }, cmd)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn probing_reads_the_banner_and_options() {
+        let current = probe_help("AirSim Studio Engine v0.2.0\n  --hdf5 <file>  also write HDF5");
+        assert_eq!(current.version.as_deref(), Some("0.2.0"));
+        assert!(current.found && current.compatible && current.hdf5);
+        assert!(current.error.is_none());
+
+        let old = probe_help("AirSim Studio Engine v0.1.9");
+        assert!(!old.compatible && !old.hdf5);
+        assert!(old.error.unwrap().contains("older than the required v0.2.0"));
+
+        let foreign = probe_help("usage: something else");
+        assert!(foreign.found && !foreign.compatible);
+        assert!(foreign.version.is_none());
+
+        let missing = probe(
+            EnginePath { path: "/nonexistent/contam_engine".to_string(), source: EngineSource::Settings },
+            Command::new("/nonexistent/contam_engine"),
+        );
+        assert!(!missing.found && !missing.compatible);
+        assert!(missing.error.is_some());
+    }
+
+    #[test]
+    fn engine_lookup_order() {
+        let dir = TestDir::new();
+        let bundled = dir.0.join(exe_name(SIDECAR_NAME));
+        std::fs::write(&bundled, "").unwrap();
+        let dirs = [dir.0.join("missing"), dir.0.clone()];
+        let env = || Some(OsString::from("/env/contam_engine"));
+
+        let found = resolve_engine_path(env(), Some("/settings/contam_engine"), &dirs);
+        assert_eq!((found.source, found.path.as_str()), (EngineSource::Env, "/env/contam_engine"));
+
+        let found = resolve_engine_path(Some(OsString::new()), Some("/settings/contam_engine"), &dirs);
+        assert_eq!((found.source, found.path.as_str()), (EngineSource::Settings, "/settings/contam_engine"));
+
+        let found = resolve_engine_path(None, Some("  "), &dirs);
+        assert_eq!(found.source, EngineSource::Bundled);
+        assert_eq!(Path::new(&found.path), bundled);
+
+        let found = resolve_engine_path(None, None, &dirs[..1]);
+        assert_eq!((found.source, found.path.as_str()), (EngineSource::Path, SIDECAR_NAME));
+    }
+}
@@ -0,0 +1,332 @@
+//! Spawning and supervising `contam_engine` child processes.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::io::ErrorKind;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::error::{EngineError, EngineLogs};
+use crate::limits::{self, RunLimits};
+use crate::logging;
+use crate::options::RunOptions;
+use crate::progress::{self, Progress, ProgressTracker};
+use crate::results::EngineResult;
+use crate::retry::SolveAttempt;
+use crate::summary::RunSummary;
+use crate::workdir::RunFiles;
+
+/// How often a running engine is polled for exit or cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Final state of an engine run, as reported to the frontend.
+#[allow(clippy::large_enum_variant)] // Completed is the common case; there is one outcome per run
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum RunOutcome {
+    /// `partial` is set when the engine exited with code 2 (steady solve not
+    /// converged, or transient run incomplete) but still wrote its output.
+    Completed {
+        /// Parsed output, kept in the `ResultStore` and served in slices; too
+        /// large to send to the webview
+        #[serde(skip)]
+        result: Arc<EngineResult>,
+        partial: bool,
+        /// `solver.maxResidual` from steady output [kg/s]
+        max_residual: Option<f64>,
+        /// Parsed from `logs.stdout`; empty for quiet runs. Segmented runs fill in
+        /// the whole window and step count
+        summary: RunSummary,
+        logs: EngineLogs,
+        /// Set when the result was served from the run history instead of running the engine
+        cached_run_id: Option<String>,
+        /// Every solve of a run with a `RetryPolicy`; empty otherwise
+        attempts: Vec<SolveAttempt>,
+    },
+    Failed { error: EngineError },
+    Cancelled,
+}
+
+impl RunOutcome {
+    /// Process output of the run; `None` if it was cancelled.
+    pub fn logs(&self) -> Option<&EngineLogs> {
+        match self {
+            RunOutcome::Completed { logs, .. } => Some(logs),
+            RunOutcome::Failed { error } => Some(error.logs()),
+            RunOutcome::Cancelled => None,
+        }
+    }
+}
+
+/// A spawned engine process with its stdout/stderr being drained in the background.
+pub struct EngineProcess {
+    child: Child,
+    started: Instant,
+    limits: RunLimits,
+    stdout: JoinHandle<String>,
+    stderr: JoinHandle<String>,
+}
+
+/// Start the engine (`cmd` names the program only) on `files.input`. Returns as
+/// soon as the process is spawned; `on_progress` is then called from a reader
+/// thread as stdout arrives.
+pub fn spawn<F>(
+    mut cmd: Command,
+    files: &RunFiles,
+    options: &RunOptions,
+    limits: RunLimits,
+    on_progress: F,
+) -> Result<EngineProcess, EngineError>
+where
+    F: FnMut(Progress) + Send + 'static,
+{
+    let engine_path = cmd.get_program().to_string_lossy().into_owned();
+    cmd.arg("-i")
+        .arg(&files.input)
+        .arg("-o")
+        .arg(&files.output)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    options.apply(&mut cmd);
+    limits.apply(&mut cmd);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW: no console window flashes up for the engine
+        cmd.creation_flags(0x0800_0000);
+    }
+
+    let spawned = cmd.spawn().inspect_err(|e| log::error!("Failed to start engine {:?}: {}", cmd, e));
+    let mut child = spawned.map_err(|e| match e.kind() {
+        ErrorKind::NotFound => EngineError::EngineNotFound {
+            path: engine_path.clone(),
+            logs: EngineLogs::default(),
+        },
+        _ => EngineError::SpawnFailed {
+            path: engine_path.clone(),
+            message: e.to_string(),
+            logs: EngineLogs::default(),
+        },
+    })?;
+    log::info!("Engine started (pid {}): {:?}", child.id(), cmd);
+
+    // Read both pipes on their own threads so a chatty engine never blocks on a full pipe
+    let stdout = follow_stdout(child.stdout.take(), on_progress);
+    let stderr = drain(child.stderr.take());
+
+    Ok(EngineProcess { child, started: Instant::now(), limits, stdout, stderr })
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
+/// Run the engine to completion on `input`: write the temp input file, spawn
+/// and supervise. `files` is consumed so the temp files are removed on return.
+pub fn execute<F>(
+    cmd: Command,
+    files: RunFiles,
+    input: &str,
+    options: &RunOptions,
+    limits: RunLimits,
+    cancel: &AtomicBool,
+    on_progress: F,
+) -> RunOutcome
+where
+    F: FnMut(Progress) + Send + 'static,
+{
+    if let Err(e) = files.write_input(input) {
+        return RunOutcome::Failed { error: EngineError::io(format!("Failed to write input file: {}", e)) };
+    }
+    match spawn(cmd, &files, options, limits, on_progress) {
+        Ok(process) => wait(process, &files, cancel),
+        Err(error) => RunOutcome::Failed { error },
+    }
+}
+
+/// Read stdout segment by segment, splitting on `\r` as well as `\n` because
+/// progress lines are rewritten in place with a carriage return. Progress
+/// lines are reported through `on_progress` and left out of the returned log,
+/// which would otherwise grow by one line per time step.
+fn follow_stdout<R, F>(pipe: Option<R>, mut on_progress: F) -> JoinHandle<String>
+where
+    R: Read + Send + 'static,
+    F: FnMut(Progress) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut log = String::new();
+        let Some(pipe) = pipe else { return log };
+        let mut reader = BufReader::new(pipe);
+        let mut tracker = ProgressTracker::new();
+        let mut segment = Vec::new();
+        let mut keep = |segment: &[u8], log: &mut String| {
+            let line = String::from_utf8_lossy(segment);
+            if let Some(progress) = tracker.feed(&line) {
+                on_progress(progress);
+            }
+            if !progress::is_progress_line(&line) {
+                log.push_str(&line);
+                log.push('\n');
+            }
+        };
+        loop {
+            let buf = match reader.fill_buf() {
+                Ok([]) | Err(_) => break,
+                Ok(buf) => buf,
+            };
+            let len = buf.len();
+            for &b in buf {
+                match b {
+                    // A bare `\r` only precedes a progress line; nothing to keep
+                    b'\r' if segment.is_empty() => {}
+                    b'\r' | b'\n' => {
+                        keep(&segment, &mut log);
+                        segment.clear();
+                    }
+                    _ => segment.push(b),
+                }
+            }
+            reader.consume(len);
+        }
+        if !segment.is_empty() {
+            keep(&segment, &mut log);
+        }
+        log
+    })
+}
+
+/// How the supervising loop in `wait` ended.
+enum Exit {
+    /// With the engine's peak resident set [bytes], where known
+    Exited(ExitStatus, Option<u64>),
+    Cancelled,
+    TimedOut(Duration),
+    WaitFailed(std::io::Error),
+}
+
+/// Block until the engine exits, `cancel` is set or the timeout expires, then
+/// collect its result.
+pub fn wait(mut process: EngineProcess, files: &RunFiles, cancel: &AtomicBool) -> RunOutcome {
+    let timeout = process.limits.timeout();
+    let exit = loop {
+        if cancel.load(Ordering::SeqCst) {
+            break Exit::Cancelled;
+        }
+        if let Some(timeout) = timeout.filter(|&t| process.started.elapsed() >= t) {
+            break Exit::TimedOut(timeout);
+        }
+        match limits::try_wait(&mut process.child) {
+            Ok(Some((status, peak_rss))) => break Exit::Exited(status, peak_rss),
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => break Exit::WaitFailed(e),
+        }
+    };
+    // Not reaped yet unless it exited
+    if !matches!(exit, Exit::Exited(..)) {
+        let _ = process.child.kill();
+        let _ = process.child.wait();
+    }
+
+    // The child is gone, so its pipes are closed and the reader threads finish
+    let stdout = process.stdout.join().unwrap_or_default();
+    let stderr = process.stderr.join().unwrap_or_default();
+    log_exit(process.child.id(), &exit, process.started.elapsed(), &stdout, &stderr);
+
+    let (status, peak_rss) = match exit {
+        Exit::Exited(status, peak_rss) => (status, peak_rss),
+        Exit::Cancelled => return RunOutcome::Cancelled,
+        Exit::TimedOut(timeout) => {
+            let logs = EngineLogs { stdout, stderr, exit_code: None };
+            return RunOutcome::Failed { error: EngineError::TimedOut { timeout_secs: timeout.as_secs(), logs } };
+        }
+        Exit::WaitFailed(e) => {
+            let logs = EngineLogs { stdout, stderr, exit_code: None };
+            let message = format!("Failed to wait for engine: {}", e);
+            return RunOutcome::Failed { error: EngineError::Io { message, logs } };
+        }
+    };
+
+    let code = status.code();
+    let keeps_output = code == Some(0) || (code == Some(2) && files.output.exists());
+    let oom = process.limits.is_out_of_memory(&status, &stderr, peak_rss);
+    let logs = EngineLogs { stdout, stderr, exit_code: code };
+    if !keeps_output && limits::hit_cpu_limit(&status) {
+        let error = EngineError::TimedOut { timeout_secs: timeout.map_or(0, |t| t.as_secs()), logs };
+        return RunOutcome::Failed { error };
+    }
+    if !keeps_output && oom {
+        let memory_limit_mb = process.limits.memory_limit_mb.filter(|&mb| mb > 0);
+        return RunOutcome::Failed { error: EngineError::OutOfMemory { memory_limit_mb, logs } };
+    }
+    exit_outcome(code, files, logs)
+}
+
+/// Outcome of an engine that exited with `code` (`None`: killed by a signal)
+/// for a reason other than a resource limit.
+pub fn exit_outcome(code: Option<i32>, files: &RunFiles, logs: EngineLogs) -> RunOutcome {
+    let error = match code {
+        Some(0) => return read_output(files, logs, false),
+        // main.cpp still writes the last state before exiting with 2; keep it for debugging
+        Some(2) if files.output.exists() => return read_output(files, logs, true),
+        Some(1) => EngineError::InputError { logs },
+        Some(2) => EngineError::NotConverged { logs },
+        _ => EngineError::Crashed { logs },
+    };
+    RunOutcome::Failed { error }
+}
+
+fn log_exit(pid: u32, exit: &Exit, elapsed: Duration, stdout: &str, stderr: &str) {
+    let how = match exit {
+        Exit::Exited(status, _) => status.to_string(),
+        Exit::Cancelled => "cancelled".to_string(),
+        Exit::TimedOut(timeout) => format!("timed out after {}s", timeout.as_secs()),
+        Exit::WaitFailed(e) => format!("wait failed: {}", e),
+    };
+    log::info!("Engine pid {} finished in {:.2}s: {}", pid, elapsed.as_secs_f64(), how);
+    if !stdout.is_empty() {
+        log::info!("Engine pid {} stdout:\n{}", pid, logging::output_tail(stdout).trim_end());
+    }
+    if !stderr.is_empty() {
+        log::info!("Engine pid {} stderr:\n{}", pid, logging::output_tail(stderr).trim_end());
+    }
+}
+
+/// Parse the output file of a finished run, which also checks that it is
+/// valid engine output.
+fn read_output(files: &RunFiles, logs: EngineLogs, partial: bool) -> RunOutcome {
+    let output = match File::open(&files.output) {
+        Ok(file) => BufReader::new(file),
+        Err(e) => {
+            let error = EngineError::OutputMissing { message: e.to_string(), logs };
+            return RunOutcome::Failed { error };
+        }
+    };
+    let result = match EngineResult::from_reader(output, Some(&files.dir)) {
+        Ok(result) => result,
+        Err(e) => {
+            let error = EngineError::InvalidOutput { message: e, logs };
+            return RunOutcome::Failed { error };
+        }
+    };
+    let max_residual = result.max_residual();
+    RunOutcome::Completed {
+        result: Arc::new(result),
+        partial,
+        max_residual,
+        summary: RunSummary::parse(&logs.stdout),
+        logs,
+        cached_run_id: None,
+        attempts: Vec::new(),
+    }
+}
@@ -0,0 +1,103 @@
+//! Typed failures of an engine run, serialized to the frontend as
+//! `{ kind: "...", ... }` so each case can get its own message.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::validation::SchemaViolation;
+
+/// Raw process output kept with every failure. Empty when the engine never started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineLogs {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum EngineError {
+    /// The input failed schema validation; the engine was not started
+    InvalidInput { errors: Vec<SchemaViolation>, logs: EngineLogs },
+    /// The `contam_engine` executable does not exist at the resolved path
+    EngineNotFound { path: String, logs: EngineLogs },
+    /// The executable exists but could not be started
+    SpawnFailed { path: String, message: String, logs: EngineLogs },
+    /// Exit code 1: the input could not be read or parsed, or the engine threw
+    InputError { logs: EngineLogs },
+    /// Exit code 2 without a usable output file: steady solve did not converge
+    /// or the transient run stopped early
+    NotConverged { logs: EngineLogs },
+    /// The engine exited successfully but wrote no output file
+    OutputMissing { message: String, logs: EngineLogs },
+    /// The output file is not valid JSON or does not have the `JsonWriter` layout
+    InvalidOutput { message: String, logs: EngineLogs },
+    /// Killed after exceeding the wall-clock (or CPU) limit
+    TimedOut { timeout_secs: u64, logs: EngineLogs },
+    /// Aborted by an allocation failure, usually under the memory cap
+    OutOfMemory { memory_limit_mb: Option<u64>, logs: EngineLogs },
+    /// Any other abnormal exit, e.g. killed by a signal
+    Crashed { logs: EngineLogs },
+    /// The backend itself failed to prepare or supervise the run
+    Io { message: String, logs: EngineLogs },
+}
+
+impl EngineError {
+    pub fn io(message: String) -> Self {
+        EngineError::Io { message, logs: EngineLogs::default() }
+    }
+
+    pub fn logs(&self) -> &EngineLogs {
+        match self {
+            EngineError::InvalidInput { logs, .. }
+            | EngineError::EngineNotFound { logs, .. }
+            | EngineError::SpawnFailed { logs, .. }
+            | EngineError::InputError { logs }
+            | EngineError::NotConverged { logs }
+            | EngineError::OutputMissing { logs, .. }
+            | EngineError::InvalidOutput { logs, .. }
+            | EngineError::TimedOut { logs, .. }
+            | EngineError::OutOfMemory { logs, .. }
+            | EngineError::Crashed { logs }
+            | EngineError::Io { logs, .. } => logs,
+        }
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::InvalidInput { errors, .. } => {
+                write!(f, "Input does not match the topology schema")?;
+                if let Some(first) = errors.first() {
+                    write!(f, ": {}", first)?;
+                }
+                if errors.len() > 1 {
+                    write!(f, " (and {} more)", errors.len() - 1)?;
+                }
+                Ok(())
+            }
+            EngineError::EngineNotFound { path, .. } => write!(f, "Engine not found: '{}'", path),
+            EngineError::SpawnFailed { path, message, .. } => {
+                write!(f, "Failed to run engine '{}': {}", path, message)
+            }
+            EngineError::InputError { .. } => write!(f, "Engine rejected the input (exit code 1)"),
+            EngineError::NotConverged { .. } => write!(f, "Engine did not converge (exit code 2)"),
+            EngineError::OutputMissing { message, .. } => write!(f, "Failed to read output file: {}", message),
+            EngineError::InvalidOutput { message, .. } => write!(f, "Output file is not valid engine output: {}", message),
+            EngineError::TimedOut { timeout_secs, .. } => {
+                write!(f, "Engine timed out after {} s", timeout_secs)
+            }
+            EngineError::OutOfMemory { memory_limit_mb: Some(mb), .. } => {
+                write!(f, "Engine exceeded the {} MiB memory limit", mb)
+            }
+            EngineError::OutOfMemory { memory_limit_mb: None, .. } => write!(f, "Engine ran out of memory"),
+            EngineError::Crashed { logs } => write!(f, "Engine failed (exit code {:?})", logs.exit_code),
+            EngineError::Io { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
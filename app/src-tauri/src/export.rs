@@ -0,0 +1,423 @@
+//! Engine results as tables for other tools: CSV in the layout of the app's
+//! own CSV export, XLSX workbooks, and SQLite databases for querying many
+//! runs at once.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+use rust_xlsxwriter::{Workbook, Worksheet};
+
+use crate::results::{EngineResult, SteadyResult, TransientResult};
+
+/// Data rows per worksheet, below the header row.
+const MAX_XLSX_ROWS: usize = 1_048_575;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+    Sqlite,
+}
+
+impl ExportFormat {
+    /// From a file extension: `csv`, `xlsx`, or `sqlite`/`sqlite3`/`db`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "xlsx" => Some(ExportFormat::Xlsx),
+            "sqlite" | "sqlite3" | "db" => Some(ExportFormat::Sqlite),
+            _ => None,
+        }
+    }
+
+    #[cfg(any(feature = "cli", test))]
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// Write `result` to `path`, replacing any file there.
+pub fn export(result: &EngineResult, format: ExportFormat, path: &Path) -> Result<(), String> {
+    let written = match format {
+        ExportFormat::Csv => write_csv(result, path),
+        ExportFormat::Xlsx => write_xlsx(result, path),
+        ExportFormat::Sqlite => write_sqlite(result, path),
+    };
+    written.map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn write_csv(result: &EngineResult, path: &Path) -> Result<(), String> {
+    let mut out = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+    match result {
+        EngineResult::Steady(steady) => steady_csv(steady, &mut out),
+        EngineResult::Transient(transient) => transient_csv(transient, &mut out),
+    }
+    .and_then(|()| out.flush())
+    .map_err(|e| e.to_string())
+}
+
+fn steady_csv(steady: &SteadyResult, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "# Node Results")?;
+    writeln!(out, "ID,Name,Pressure(Pa),Density(kg/m³),Temperature(K),Elevation(m)")?;
+    for n in &steady.nodes {
+        let name = csv_field(&n.name);
+        writeln!(out, "{},{},{:.6},{:.6},{:.2},{:.2}", n.id, name, n.pressure, n.density, n.temperature, n.elevation)?;
+    }
+    writeln!(out)?;
+    writeln!(out, "# Link Results")?;
+    writeln!(out, "ID,From,To,MassFlow(kg/s),VolumeFlow(m³/s)")?;
+    for l in &steady.links {
+        writeln!(out, "{},{},{},{:.8},{:.8}", l.id, l.from, l.to, l.mass_flow, l.volume_flow)?;
+    }
+    Ok(())
+}
+
+/// Pressures and concentrations of the non-ambient nodes, one row per step.
+fn transient_csv(transient: &TransientResult, out: &mut impl Write) -> std::io::Result<()> {
+    let zones = zone_indices(transient);
+    let mut header = vec!["Time(s)".to_string()];
+    header.extend(zones.iter().map(|&n| csv_field(&format!("P_{}(Pa)", transient.nodes[n].name))));
+    for &n in &zones {
+        for species in &transient.species {
+            header.push(csv_field(&format!("C_{}_{}(kg/m³)", transient.nodes[n].name, species.name)));
+        }
+    }
+    writeln!(out, "{}", header.join(","))?;
+
+    for (step, time) in transient.times().iter().enumerate() {
+        write!(out, "{:.1}", time)?;
+        for &n in &zones {
+            write!(out, ",{:.6}", transient.pressure(step, n))?;
+        }
+        for &n in &zones {
+            for s in 0..transient.species.len() {
+                write!(out, ",{:.6e}", transient.concentration(step, n, s))?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Quote a field containing a separator, quote or line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn zone_indices(transient: &TransientResult) -> Vec<usize> {
+    (transient.nodes.iter().enumerate())
+        .filter(|(_, node)| node.node_type != "ambient")
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn write_xlsx(result: &EngineResult, path: &Path) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    match result {
+        EngineResult::Steady(steady) => steady_xlsx(steady, &mut workbook),
+        EngineResult::Transient(transient) => transient_xlsx(transient, &mut workbook),
+    }
+    .map_err(|e| e.to_string())?;
+    workbook.save(path).map_err(|e| e.to_string())
+}
+
+type XlsxResult = Result<(), rust_xlsxwriter::XlsxError>;
+
+fn steady_xlsx(steady: &SteadyResult, workbook: &mut Workbook) -> XlsxResult {
+    let header = ["ID", "Name", "Pressure(Pa)", "Density(kg/m³)", "Temperature(K)", "Elevation(m)"];
+    let sheet = header_sheet(workbook, "Nodes", &header)?;
+    for (row, n) in (1..).zip(&steady.nodes) {
+        sheet.write_number(row, 0, n.id)?;
+        sheet.write_string(row, 1, &n.name)?;
+        for (col, value) in (2..).zip([n.pressure, n.density, n.temperature, n.elevation]) {
+            number(sheet, row, col, value)?;
+        }
+    }
+    let sheet = header_sheet(workbook, "Links", &["ID", "From", "To", "MassFlow(kg/s)", "VolumeFlow(m³/s)"])?;
+    for (row, l) in (1..).zip(&steady.links) {
+        for (col, id) in (0..).zip([l.id, l.from, l.to]) {
+            sheet.write_number(row, col, id)?;
+        }
+        number(sheet, row, 3, l.mass_flow)?;
+        number(sheet, row, 4, l.volume_flow)?;
+    }
+    let sheet = header_sheet(workbook, "Solver", &["Converged", "Iterations", "MaxResidual(kg/s)"])?;
+    sheet.write_boolean(1, 0, steady.solver.converged)?;
+    sheet.write_number(1, 1, steady.solver.iterations)?;
+    if let Some(residual) = steady.solver.max_residual {
+        number(sheet, 1, 2, residual)?;
+    }
+    Ok(())
+}
+
+/// One sheet per quantity, one row per step.
+fn transient_xlsx(transient: &TransientResult, workbook: &mut Workbook) -> XlsxResult {
+    if transient.step_count() > MAX_XLSX_ROWS {
+        let message = format!("{} steps do not fit in a worksheet; export to SQLite instead", transient.step_count());
+        return Err(rust_xlsxwriter::XlsxError::ParameterError(message));
+    }
+    let names: Vec<String> = transient.nodes.iter().map(|n| format!("{}(Pa)", n.name)).collect();
+    let sheet = time_sheet(workbook, "Pressures", transient.times(), &names)?;
+    for row in 0..transient.step_count() {
+        for n in 0..transient.nodes.len() {
+            number(sheet, row as u32 + 1, n as u16 + 1, transient.pressure(row, n))?;
+        }
+    }
+
+    // Transient output identifies links by their position in the input
+    let names: Vec<String> = (0..transient.link_count()).map(|l| format!("Link#{}(kg/s)", l)).collect();
+    let sheet = time_sheet(workbook, "Flows", transient.times(), &names)?;
+    for row in 0..transient.step_count() {
+        for l in 0..transient.link_count() {
+            number(sheet, row as u32 + 1, l as u16 + 1, transient.mass_flow(row, l))?;
+        }
+    }
+
+    if !transient.species.is_empty() {
+        let zones = zone_indices(transient);
+        let names: Vec<String> = (zones.iter())
+            .flat_map(|&n| transient.species.iter().map(move |s| (n, s)))
+            .map(|(n, species)| format!("{}_{}(kg/m³)", transient.nodes[n].name, species.name))
+            .collect();
+        let sheet = time_sheet(workbook, "Concentrations", transient.times(), &names)?;
+        for row in 0..transient.step_count() {
+            let values = zones.iter().flat_map(|&n| (0..transient.species.len()).map(move |s| (n, s)));
+            for (col, (n, s)) in (1..).zip(values) {
+                number(sheet, row as u32 + 1, col, transient.concentration(row, n, s))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn header_sheet<'a>(
+    workbook: &'a mut Workbook,
+    name: &str,
+    header: &[&str],
+) -> Result<&'a mut Worksheet, rust_xlsxwriter::XlsxError> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(name)?;
+    for (col, title) in (0..).zip(header) {
+        sheet.write_string(0, col, *title)?;
+    }
+    Ok(sheet)
+}
+
+/// A sheet with the output times filled in, and headers for `columns` after them.
+fn time_sheet<'a>(
+    workbook: &'a mut Workbook,
+    name: &str,
+    times: &[f64],
+    columns: &[String],
+) -> Result<&'a mut Worksheet, rust_xlsxwriter::XlsxError> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(name)?;
+    sheet.write_string(0, 0, "Time(s)")?;
+    for (col, title) in (1..).zip(columns) {
+        sheet.write_string(0, col, title)?;
+    }
+    for (row, &time) in (1..).zip(times) {
+        sheet.write_number(row, 0, time)?;
+    }
+    Ok(sheet)
+}
+
+/// NaN and infinite values, which Excel cannot store, are left blank.
+fn number(sheet: &mut Worksheet, row: u32, col: u16, value: f64) -> XlsxResult {
+    if value.is_finite() {
+        sheet.write_number(row, col, value)?;
+    }
+    Ok(())
+}
+
+const STEADY_SCHEMA: &str = "
+    CREATE TABLE solver (converged INTEGER NOT NULL, iterations INTEGER NOT NULL, max_residual REAL);
+    CREATE TABLE nodes (
+        id INTEGER PRIMARY KEY, name TEXT NOT NULL,
+        pressure REAL, density REAL, temperature REAL, elevation REAL
+    );
+    CREATE TABLE links (
+        id INTEGER PRIMARY KEY, from_node INTEGER NOT NULL, to_node INTEGER NOT NULL,
+        mass_flow REAL, volume_flow REAL
+    );
+";
+
+const TRANSIENT_SCHEMA: &str = "
+    CREATE TABLE run (completed INTEGER NOT NULL, total_steps INTEGER NOT NULL);
+    CREATE TABLE nodes (id INTEGER PRIMARY KEY, name TEXT NOT NULL, type TEXT NOT NULL);
+    CREATE TABLE species (id INTEGER PRIMARY KEY, name TEXT NOT NULL, molar_mass REAL);
+    CREATE TABLE steps (
+        step INTEGER PRIMARY KEY, time REAL NOT NULL, converged INTEGER NOT NULL, iterations INTEGER NOT NULL
+    );
+    CREATE TABLE pressures (step INTEGER NOT NULL, node_id INTEGER NOT NULL, pressure REAL);
+    CREATE TABLE mass_flows (step INTEGER NOT NULL, link_index INTEGER NOT NULL, mass_flow REAL);
+    CREATE TABLE concentrations (
+        step INTEGER NOT NULL, node_id INTEGER NOT NULL, species_id INTEGER NOT NULL, concentration REAL
+    );
+";
+
+/// Long tables keyed by step, node, link position and species, so runs can
+/// be compared with SQL. NaN values are stored as NULL.
+fn write_sqlite(result: &EngineResult, path: &Path) -> Result<(), String> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+        _ => {}
+    }
+    let mut db = Connection::open(path).map_err(|e| e.to_string())?;
+    let tx = db.transaction().map_err(|e| e.to_string())?;
+    match result {
+        EngineResult::Steady(steady) => steady_sqlite(steady, &tx),
+        EngineResult::Transient(transient) => transient_sqlite(transient, &tx),
+    }
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn steady_sqlite(steady: &SteadyResult, db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(STEADY_SCHEMA)?;
+    let solver = &steady.solver;
+    let values = params![solver.converged, solver.iterations, solver.max_residual];
+    db.execute("INSERT INTO solver VALUES (?1, ?2, ?3)", values)?;
+    let mut insert = db.prepare("INSERT INTO nodes VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+    for n in &steady.nodes {
+        insert.execute(params![n.id, n.name, finite(n.pressure), finite(n.density), n.temperature, n.elevation])?;
+    }
+    let mut insert = db.prepare("INSERT INTO links VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    for l in &steady.links {
+        insert.execute(params![l.id, l.from, l.to, finite(l.mass_flow), finite(l.volume_flow)])?;
+    }
+    Ok(())
+}
+
+fn transient_sqlite(transient: &TransientResult, db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(TRANSIENT_SCHEMA)?;
+    db.execute("INSERT INTO run VALUES (?1, ?2)", params![transient.completed, transient.total_steps])?;
+    let mut insert = db.prepare("INSERT INTO nodes VALUES (?1, ?2, ?3)")?;
+    for n in &transient.nodes {
+        insert.execute(params![n.id, n.name, n.node_type])?;
+    }
+    let mut insert = db.prepare("INSERT INTO species VALUES (?1, ?2, ?3)")?;
+    for s in &transient.species {
+        insert.execute(params![s.id, s.name, s.molar_mass])?;
+    }
+
+    let mut steps = db.prepare("INSERT INTO steps VALUES (?1, ?2, ?3, ?4)")?;
+    let mut pressures = db.prepare("INSERT INTO pressures VALUES (?1, ?2, ?3)")?;
+    let mut flows = db.prepare("INSERT INTO mass_flows VALUES (?1, ?2, ?3)")?;
+    let mut concentrations = db.prepare("INSERT INTO concentrations VALUES (?1, ?2, ?3, ?4)")?;
+    for step in 0..transient.step_count() {
+        let Some(state) = transient.step(step) else { break };
+        steps.execute(params![step, state.time, state.airflow.converged, state.airflow.iterations])?;
+        for (node, pressure) in transient.nodes.iter().zip(&state.airflow.pressures) {
+            pressures.execute(params![step, node.id, finite(*pressure)])?;
+        }
+        for (link, flow) in state.airflow.mass_flows.iter().enumerate() {
+            flows.execute(params![step, link, finite(*flow)])?;
+        }
+        for (node, by_species) in transient.nodes.iter().zip(&state.concentrations) {
+            for (species, concentration) in transient.species.iter().zip(by_species) {
+                concentrations.execute(params![step, node.id, species.id, finite(*concentration)])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn finite(value: f64) -> Option<f64> {
+    value.is_finite().then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::runner::MockRunner;
+    use crate::testing::TestDir;
+
+    fn case_output(case: &str, dir: &TestDir) -> EngineResult {
+        let file = File::open(MockRunner::case_dir(case).join("output.json")).unwrap();
+        EngineResult::from_reader(file, Some(&dir.0)).unwrap()
+    }
+
+    fn export_all(result: &EngineResult, dir: &TestDir) -> Vec<PathBuf> {
+        let formats = [ExportFormat::Csv, ExportFormat::Xlsx, ExportFormat::Sqlite];
+        formats
+            .into_iter()
+            .map(|format| {
+                let path = dir.0.join(format!("export.{}", format.extension()));
+                export(result, format, &path).unwrap();
+                assert_eq!(ExportFormat::from_path(&path), Some(format));
+                assert!(std::fs::metadata(&path).unwrap().len() > 0);
+                path
+            })
+            .collect()
+    }
+
+    #[test]
+    fn formats_follow_the_extension() {
+        assert_eq!(ExportFormat::from_path(Path::new("a/b.CSV")), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::from_path(Path::new("runs.db")), Some(ExportFormat::Sqlite));
+        assert_eq!(ExportFormat::from_path(Path::new("runs.sqlite3")), Some(ExportFormat::Sqlite));
+        assert_eq!(ExportFormat::from_path(Path::new("result.json")), None);
+        assert_eq!(ExportFormat::from_path(Path::new("result")), None);
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("Living room"), "Living room");
+        assert_eq!(csv_field("Office, 2F"), "\"Office, 2F\"");
+        assert_eq!(csv_field("6\" duct"), "\"6\"\" duct\"");
+    }
+
+    #[test]
+    fn transient_exports_hold_every_step() {
+        let dir = TestDir::new();
+        let result = case_output("case02_co2_source", &dir);
+        let EngineResult::Transient(transient) = &result else { panic!("case02 is transient") };
+        export_all(&result, &dir);
+
+        let csv = std::fs::read_to_string(dir.0.join("export.csv")).unwrap();
+        assert_eq!(csv.lines().count(), transient.step_count() + 1);
+        let zones = zone_indices(transient).len();
+        assert_eq!(csv.lines().next().unwrap().split(',').count(), 1 + zones * (1 + transient.species.len()));
+
+        let db = Connection::open(dir.0.join("export.sqlite")).unwrap();
+        let count = |table: &str| -> usize {
+            db.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(count("steps"), transient.step_count());
+        assert_eq!(count("pressures"), transient.step_count() * transient.nodes.len());
+        assert_eq!(count("mass_flows"), transient.step_count() * transient.link_count());
+    }
+
+    #[test]
+    fn steady_exports_hold_every_node_and_link() {
+        let dir = TestDir::new();
+        let result = case_output("case01_3room", &dir);
+        let EngineResult::Steady(steady) = &result else { panic!("case01 is steady") };
+        export_all(&result, &dir);
+
+        let csv = std::fs::read_to_string(dir.0.join("export.csv")).unwrap();
+        // Two headings, two header rows and a blank line around the rows
+        assert_eq!(csv.lines().count(), 5 + steady.nodes.len() + steady.links.len());
+
+        let db = Connection::open(dir.0.join("export.sqlite")).unwrap();
+        let converged: bool = db.query_row("SELECT converged FROM solver", [], |row| row.get(0)).unwrap();
+        assert_eq!(converged, steady.solver.converged);
+        let links: usize = db.query_row("SELECT COUNT(*) FROM links", [], |row| row.get(0)).unwrap();
+        assert_eq!(links, steady.links.len());
+    }
+}
@@ -0,0 +1,414 @@
+//! The Tauri app: commands and events for the webview, and app setup.
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::ipc::Response;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind, TimezoneStrategy};
+use tauri_plugin_shell::ShellExt;
+
+use crate::commands::{self, RunArgs, RunFinished};
+use crate::discovery::{self, EngineInfo, EngineInfoCache, EnginePath, EngineSource};
+use crate::history::{HistoryEntry, HistoryRun, HistoryStore};
+use crate::launch::Launcher;
+use crate::limits::RunLimits;
+use crate::live::{self, LiveSolver, LiveUpdate};
+use crate::logging;
+use crate::options::RunOptions;
+use crate::progress::Progress;
+use crate::result_store::{ResultOverview, ResultStore};
+use crate::results::{EngineResult, SliceQuery};
+use crate::retry::RetryPolicy;
+use crate::runner::ProcessRunner;
+use crate::runs::{JobManager, RunStatus};
+use crate::segments::{FinishedSegment, SegmentPolicy};
+use crate::settings::{Settings, SettingsStore};
+use crate::validation::{self, SchemaViolation};
+use crate::workdir::WorkDir;
+
+/// Event emitted once a run queued by `run_engine` has exited, failed or been cancelled.
+/// Completed runs carry no output; it is fetched with `get_result`.
+const RUN_FINISHED_EVENT: &str = "engine-run-finished";
+
+/// Event emitted while a transient run advances, throttled by `ProgressTracker`.
+const RUN_PROGRESS_EVENT: &str = "engine-run-progress";
+
+/// Event emitted as each segment of a segmented run finishes; its output is
+/// fetched with `get_result` under the segment's result ID.
+const RUN_SEGMENT_EVENT: &str = "engine-run-segment";
+
+/// Event emitted with the pressures and flows of each live solve.
+const LIVE_SOLVE_EVENT: &str = "live-solve";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunProgress {
+    run_id: String,
+    #[serde(flatten)]
+    progress: Progress,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunSegment {
+    run_id: String,
+    /// `<runId>:segment`, holding the latest segment of the run until the next
+    /// one finishes, so long runs do not push other results out of the store
+    result_id: String,
+    #[serde(flatten)]
+    segment: FinishedSegment,
+}
+
+/// Queue an engine run and return its run ID immediately.
+/// Progress is reported through `engine-run-progress` and the result through
+/// `engine-run-finished`. Limits not given here come from the user's settings.
+/// Input that fails schema validation finishes as `invalidInput` without
+/// starting the engine. Every run that reaches the engine is recorded in the run history. An
+/// identical earlier run (same model, solver, engine version, retry, warm start
+/// and segments) that produced whole output is returned from the history
+/// without spawning the engine, unless `fresh` is set.
+/// With `retry`, a solve that does not converge is repeated with the other
+/// solver method and every attempt is listed on the result. With
+/// `warm_start`, node pressures of the latest converged run of the same
+/// network in the history are the solver's initial guess. With `segments`, a
+/// transient window is run in segments that resume from checkpoints and are
+/// reported through `engine-run-segment`; a cancelled or failed run keeps
+/// the segments already finished as a partial result.
+#[tauri::command]
+async fn run_engine(
+    app: AppHandle,
+    input: String,
+    options: Option<RunOptions>,
+    limits: Option<RunLimits>,
+    fresh: Option<bool>,
+    retry: Option<RetryPolicy>,
+    warm_start: Option<bool>,
+    segments: Option<SegmentPolicy>,
+) -> Result<String, String> {
+    // Probing the engine and looking the run up in the history read files and
+    // can spawn the engine, so they stay off the main thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = app.state::<SettingsStore>().get();
+        let runner = process_runner(&app);
+        let progress_app = app.clone();
+        let segment_app = app.clone();
+        let launcher = Launcher {
+            jobs: app.state::<JobManager>().inner().clone(),
+            workdir: app.state::<WorkDir>().inner().clone(),
+            history: app.state::<HistoryStore>().inner().clone(),
+            on_progress: Arc::new(move |run_id, progress| {
+                let _ = progress_app.emit(RUN_PROGRESS_EVENT, RunProgress { run_id: run_id.to_string(), progress });
+            }),
+            on_segment: Arc::new(move |run_id, segment| {
+                let result_id = format!("{}:segment", run_id);
+                segment_app.state::<ResultStore>().insert(&result_id, segment.result.clone());
+                let event = RunSegment { run_id: run_id.to_string(), result_id, segment: segment.clone() };
+                let _ = segment_app.emit(RUN_SEGMENT_EVENT, event);
+            }),
+        };
+        let args = RunArgs { input, options, limits, fresh, retry, warm_start, segments };
+        launcher.launch(Arc::new(runner), args.into_request(&settings))
+    })
+    .await
+    .map_err(|e| format!("Failed to start run: {}", e))?
+}
+
+/// Live mode: solve an edited steady model once edits pause, cancelling any
+/// live solve still running. Returns the edit's revision; pressures and flows
+/// arrive through `live-solve` with the revision they belong to. Live solves
+/// use the configured limits and are not recorded in the history.
+#[tauri::command]
+fn live_update(
+    live: State<'_, LiveSolver>,
+    settings: State<'_, SettingsStore>,
+    input: String,
+    options: Option<RunOptions>,
+) -> Result<u64, String> {
+    live.update(input, options.unwrap_or_default(), settings.get().run_limits())
+}
+
+/// Leave live mode: drop any pending edit and cancel the live solve.
+#[tauri::command]
+fn live_stop(live: State<'_, LiveSolver>) {
+    live.stop();
+}
+
+/// The engine configured in the settings, probed once per location.
+fn process_runner(app: &AppHandle) -> ProcessRunner {
+    let location = discovery::find_engine_path(app.state::<SettingsStore>().get().engine_path.as_deref());
+    let info = app.state::<EngineInfoCache>().get_or_probe(&location, || engine_command(app, &location));
+    let app = app.clone();
+    ProcessRunner::new(info, move || engine_command(&app, &location))
+}
+
+/// A run's result from memory, or from the history if it has been evicted.
+fn find_result(app: &AppHandle, run_id: &str) -> Result<Arc<EngineResult>, String> {
+    commands::find_result(&app.state::<ResultStore>(), &app.state::<HistoryStore>(), &app.state::<WorkDir>(), run_id)
+}
+
+/// Overview of a finished run's result: the whole steady result, or what a
+/// transient result contains. Its series are fetched with `get_result_slice`.
+#[tauri::command]
+async fn get_result(app: AppHandle, run_id: String) -> Result<ResultOverview, String> {
+    tauri::async_runtime::spawn_blocking(move || Ok(ResultOverview::from(&*find_result(&app, &run_id)?)))
+        .await
+        .map_err(|e| format!("Failed to load result: {}", e))?
+}
+
+/// Part of a transient result as little-endian f64s, laid out as described
+/// on `SliceQuery`. Sent as raw bytes, not JSON.
+#[tauri::command]
+async fn get_result_slice(app: AppHandle, run_id: String, query: SliceQuery) -> Result<Response, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        commands::result_slice(&*find_result(&app, &run_id)?, &query).map(Response::new)
+    })
+    .await
+    .map_err(|e| format!("Failed to slice result: {}", e))?
+}
+
+/// Write a run's result to `path` as CSV, XLSX or SQLite, chosen by the
+/// extension. Written from the backend's copy, step by step, so exports of
+/// long transient runs never pass through the webview.
+#[tauri::command]
+async fn export_result(app: AppHandle, run_id: String, path: PathBuf) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || commands::export_result(&*find_result(&app, &run_id)?, &path))
+        .await
+        .map_err(|e| format!("Failed to export result: {}", e))?
+}
+
+/// Check a model against the topology schema without running it.
+/// Returns the violations, empty when the input is valid.
+#[tauri::command]
+async fn validate_input(input: String) -> Result<Vec<SchemaViolation>, String> {
+    tauri::async_runtime::spawn_blocking(move || validation::validate_input(&input).err().unwrap_or_default())
+        .await
+        .map_err(|e| format!("Failed to validate input: {}", e))
+}
+
+/// Cancel a queued run, or kill a running engine process. Temp files are
+/// removed by the run's worker thread.
+#[tauri::command]
+fn cancel_run(jobs: State<'_, JobManager>, run_id: String) -> Result<(), String> {
+    commands::cancel_run(&jobs, &run_id)
+}
+
+/// All queued, running and recently finished runs, oldest first.
+#[tauri::command]
+fn list_runs(jobs: State<'_, JobManager>) -> Vec<RunStatus> {
+    jobs.list()
+}
+
+#[tauri::command]
+fn get_run_status(jobs: State<'_, JobManager>, run_id: String) -> Result<RunStatus, String> {
+    commands::run_status(&jobs, &run_id)
+}
+
+/// Wait for a run to finish and return the same payload as `engine-run-finished`.
+/// Returns `null` if `timeout_ms` elapses first.
+#[tauri::command]
+async fn wait_run(
+    jobs: State<'_, JobManager>,
+    run_id: String,
+    timeout_ms: Option<u64>,
+) -> Result<Option<RunFinished>, String> {
+    let jobs = jobs.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || commands::wait_run(&jobs, run_id, timeout_ms))
+        .await
+        .map_err(|e| format!("Failed to wait for run: {}", e))?
+}
+
+/// Locate the engine and report its version and compiled-in capabilities,
+/// so the UI can warn about a missing or incompatible engine before a run.
+#[tauri::command]
+async fn engine_info(app: AppHandle, settings: State<'_, SettingsStore>) -> Result<EngineInfo, String> {
+    let location = discovery::find_engine_path(settings.get().engine_path.as_deref());
+    let cmd = engine_command(&app, &location);
+    tauri::async_runtime::spawn_blocking(move || discovery::probe(location, cmd))
+        .await
+        .map_err(|e| format!("Failed to probe engine: {}", e))
+}
+
+/// Build the command that launches the engine. The bundled engine is run as
+/// a Tauri sidecar; user-supplied paths are run as given.
+fn engine_command(app: &AppHandle, location: &EnginePath) -> Command {
+    if location.source == EngineSource::Bundled {
+        match app.shell().sidecar(&location.path) {
+            Ok(sidecar) => return sidecar.into(),
+            Err(e) => log::warn!("Failed to resolve engine sidecar '{}': {}", location.path, e),
+        }
+    }
+    location.command()
+}
+
+/// Stored runs, newest first.
+#[tauri::command]
+async fn list_history(history: State<'_, HistoryStore>) -> Result<Vec<HistoryEntry>, String> {
+    let history = history.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || history.list())
+        .await
+        .map_err(|e| format!("Failed to list history: {}", e))
+}
+
+/// A stored run with its input, output and logs, for re-display or re-running.
+#[tauri::command]
+async fn load_history_run(history: State<'_, HistoryStore>, run_id: String) -> Result<HistoryRun, String> {
+    let history = history.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || history.load(&run_id))
+        .await
+        .map_err(|e| format!("Failed to load run: {}", e))?
+}
+
+/// Set a stored run's notes and/or tags; omitted fields are left unchanged.
+#[tauri::command]
+async fn tag_history_run(
+    history: State<'_, HistoryStore>,
+    run_id: String,
+    notes: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<HistoryEntry, String> {
+    let history = history.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || history.annotate(&run_id, notes, tags))
+        .await
+        .map_err(|e| format!("Failed to tag run: {}", e))?
+}
+
+#[tauri::command]
+async fn delete_history_run(history: State<'_, HistoryStore>, run_id: String) -> Result<(), String> {
+    let history = history.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || history.delete(&run_id))
+        .await
+        .map_err(|e| format!("Failed to delete run: {}", e))?
+}
+
+/// Delete the oldest stored runs until the history fits in `quota_mb`
+/// (default: the configured quota). Returns the deleted run IDs.
+#[tauri::command]
+async fn prune_history(
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+    quota_mb: Option<u64>,
+) -> Result<Vec<String>, String> {
+    let quota = quota_mb.map_or_else(|| settings.get().history_quota_bytes(), |mb| mb * 1024 * 1024);
+    let history = history.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || history.prune(quota))
+        .await
+        .map_err(|e| format!("Failed to prune history: {}", e))
+}
+
+/// The tail of the application log (default: last 1 MiB), for the in-app log viewer.
+#[tauri::command]
+fn read_logs(app: AppHandle, max_bytes: Option<u64>) -> Result<String, String> {
+    let dir = app.path().app_log_dir().map_err(|e| format!("Failed to locate log directory: {}", e))?;
+    logging::read_logs(&dir, max_bytes.unwrap_or(1024 * 1024))
+}
+
+#[tauri::command]
+fn get_settings(settings: State<'_, SettingsStore>) -> Settings {
+    settings.get()
+}
+
+#[tauri::command]
+fn update_settings(
+    store: State<'_, SettingsStore>,
+    jobs: State<'_, JobManager>,
+    settings: Settings,
+) -> Result<(), String> {
+    // Only settings that were saved take effect
+    let max_concurrent = settings.max_concurrent_runs;
+    store.set(settings)?;
+    jobs.set_max_concurrent(max_concurrent);
+    Ok(())
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+  tauri::Builder::default()
+    .plugin(tauri_plugin_dialog::init())
+    .plugin(tauri_plugin_fs::init())
+    .plugin(tauri_plugin_shell::init())
+    .plugin(
+      tauri_plugin_log::Builder::default()
+        .level(log::LevelFilter::Info)
+        .targets([
+          Target::new(TargetKind::Stdout),
+          Target::new(TargetKind::LogDir { file_name: Some(logging::LOG_FILE_NAME.to_string()) }),
+        ])
+        .max_file_size(logging::MAX_FILE_BYTES)
+        .rotation_strategy(RotationStrategy::KeepSome(logging::KEEP_FILES))
+        .timezone_strategy(TimezoneStrategy::UseLocal)
+        .build(),
+    )
+    .setup(|app| {
+      let config_dir = app.path().app_config_dir()?;
+      let settings = SettingsStore::load(config_dir);
+
+      // Run files from a previous session that crashed mid-run
+      let workdir = WorkDir::create(app.path().app_cache_dir()?.join("runs"))?;
+      let max_age = Duration::from_secs(settings.get().temp_max_age_hours * 60 * 60);
+      workdir.sweep(max_age);
+      let history = HistoryStore::create(app.path().app_data_dir()?.join("history"))?;
+      app.manage(EngineInfoCache::default());
+      app.manage(ResultStore::default());
+
+      // Live solves get a slot of their own and never show up in `list_runs`
+      let live_launcher = Launcher {
+        jobs: JobManager::new(1, Box::new(|_, _| {})),
+        workdir: workdir.clone(),
+        history: history.clone(),
+        on_progress: Arc::new(|_, _| {}),
+        on_segment: Arc::new(|_, _| {}),
+      };
+      let runner_app = app.handle().clone();
+      let live_app = app.handle().clone();
+      app.manage(LiveSolver::new(
+        live_launcher,
+        Box::new(move || Arc::new(process_runner(&runner_app))),
+        live::DEFAULT_DEBOUNCE,
+        Box::new(move |update: LiveUpdate| {
+          let _ = live_app.emit(LIVE_SOLVE_EVENT, update);
+        }),
+      ));
+      app.manage(workdir);
+      app.manage(history);
+
+      let handle = app.handle().clone();
+      app.manage(JobManager::new(
+        settings.get().max_concurrent_runs,
+        Box::new(move |run_id, outcome| {
+          commands::store_result(&handle.state::<ResultStore>(), run_id, outcome);
+          let finished = RunFinished { run_id: run_id.to_string(), outcome: outcome.clone() };
+          let _ = handle.emit(RUN_FINISHED_EVENT, finished);
+        }),
+      ));
+      app.manage(settings);
+      Ok(())
+    })
+    .invoke_handler(tauri::generate_handler![
+      run_engine,
+      live_update,
+      live_stop,
+      validate_input,
+      get_result,
+      get_result_slice,
+      export_result,
+      cancel_run,
+      list_runs,
+      get_run_status,
+      wait_run,
+      engine_info,
+      get_settings,
+      update_settings,
+      list_history,
+      load_history_run,
+      tag_history_run,
+      delete_history_run,
+      prune_history,
+      read_logs,
+    ])
+    .run(tauri::generate_context!())
+    .expect("error while running tauri application");
+}
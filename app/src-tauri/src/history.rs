@@ -0,0 +1,300 @@
+//! Persistent history of engine runs in the app data directory.
+//!
+//! Every run gets its own `<history>/<run_id>/` directory holding
+//! `meta.json` (the `HistoryEntry`), `input.json`, `logs.json` and, when the
+//! engine produced one, `output.json`; converged runs add `pressures.json`
+//! for warm starts. `meta.json` is written last, so a directory without it
+//! is an interrupted write and is ignored.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::RunOutcome;
+#[cfg(any(feature = "gui", test))]
+use crate::error::EngineLogs;
+use crate::options::RunOptions;
+use crate::results::EngineResult;
+use crate::retry::SolveAttempt;
+use crate::summary::RunSummary;
+use crate::runs::RunState;
+
+const META_FILE: &str = "meta.json";
+const INPUT_FILE: &str = "input.json";
+const OUTPUT_FILE: &str = "output.json";
+const LOGS_FILE: &str = "logs.json";
+/// Final node pressures of a converged run, for warm starts
+const PRESSURES_FILE: &str = "pressures.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub run_id: String,
+    /// Unix timestamp [ms] when the engine was started
+    pub started_at: u64,
+    pub duration_ms: u64,
+    pub state: RunState,
+    /// Output is the last state of a non-converged or incomplete run
+    pub partial: bool,
+    /// `solver.maxResidual` of steady output [kg/s]
+    pub max_residual: Option<f64>,
+    pub error: Option<String>,
+    pub engine_version: Option<String>,
+    pub options: RunOptions,
+    /// `cache::cache_key` of the run; `None` if it cannot be reused
+    pub cache_key: Option<String>,
+    /// `cache::model_key` of the input
+    pub model_key: Option<String>,
+    /// Run whose pressures were the initial guess, if warm-started
+    pub warm_start_run_id: Option<String>,
+    /// Solves of a run with a retry policy; empty otherwise
+    #[serde(default)]
+    pub attempts: Vec<SolveAttempt>,
+    /// Counts and solver statistics from the engine's verbose output
+    #[serde(default)]
+    pub summary: RunSummary,
+    pub notes: String,
+    pub tags: Vec<String>,
+    /// Disk usage of the run's history directory [bytes]
+    pub size_bytes: u64,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        run_id: &str,
+        started_at: u64,
+        duration: Duration,
+        outcome: &RunOutcome,
+        engine_version: Option<String>,
+        options: RunOptions,
+    ) -> Self {
+        let (partial, max_residual, error, attempts) = match outcome {
+            RunOutcome::Completed { partial, max_residual, attempts, .. } => {
+                (*partial, *max_residual, None, attempts.clone())
+            }
+            RunOutcome::Failed { error } => (false, None, Some(error.to_string()), Vec::new()),
+            RunOutcome::Cancelled => (false, None, None, Vec::new()),
+        };
+        // Segmented runs carry a summary of the whole run rather than of their last segment
+        let summary = match outcome {
+            RunOutcome::Completed { summary, .. } => summary.clone(),
+            _ => outcome.logs().map(|logs| RunSummary::parse(&logs.stdout)).unwrap_or_default(),
+        };
+        Self {
+            run_id: run_id.to_string(),
+            started_at,
+            duration_ms: duration.as_millis() as u64,
+            state: RunState::from(outcome),
+            partial,
+            max_residual,
+            error,
+            engine_version,
+            options,
+            cache_key: None,
+            model_key: None,
+            warm_start_run_id: None,
+            attempts,
+            summary,
+            notes: String::new(),
+            tags: Vec::new(),
+            size_bytes: 0,
+        }
+    }
+}
+
+/// A stored run with everything needed to reproduce it. Its result can be
+/// large and is read separately with `HistoryStore::load_result`.
+#[cfg(any(feature = "gui", test))]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRun {
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    pub input: String,
+    pub has_output: bool,
+    pub logs: Option<EngineLogs>,
+}
+
+#[derive(Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+    /// Serializes writers so concurrent runs cannot interleave a prune with a record
+    write: Arc<Mutex<()>>,
+}
+
+impl HistoryStore {
+    /// Create `dir` if needed and restrict it to the current user; stored
+    /// inputs are client building data just like the run temp files.
+    pub fn create(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+        Ok(Self { dir, write: Arc::new(Mutex::new(())) })
+    }
+
+    fn run_dir(&self, run_id: &str) -> Result<PathBuf, String> {
+        // Run IDs become directory names; only accept the UUIDs we hand out
+        uuid::Uuid::parse_str(run_id).map_err(|_| format!("Invalid run ID: {}", run_id))?;
+        Ok(self.dir.join(run_id))
+    }
+
+    /// Store a finished run. `entry.size_bytes` is filled in here.
+    pub fn record(&self, mut entry: HistoryEntry, input: &str, outcome: &RunOutcome) -> Result<(), String> {
+        let _guard = self.write.lock().unwrap();
+        let dir = self.run_dir(&entry.run_id)?;
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create history directory: {}", e))?;
+
+        write_file(&dir.join(INPUT_FILE), input)?;
+        if let RunOutcome::Completed { result, partial, .. } = outcome {
+            write_output(&dir.join(OUTPUT_FILE), result)?;
+            if !partial && result.converged() {
+                write_file(&dir.join(PRESSURES_FILE), &to_json(&result.final_pressures())?)?;
+            }
+        }
+        if let Some(logs) = outcome.logs() {
+            write_file(&dir.join(LOGS_FILE), &to_json(logs)?)?;
+        }
+        entry.size_bytes = dir_size(&dir);
+        write_file(&dir.join(META_FILE), &to_json(&entry)?)
+    }
+
+    /// All stored runs, newest first.
+    pub fn list(&self) -> Vec<HistoryEntry> {
+        let Ok(dirs) = std::fs::read_dir(&self.dir) else { return Vec::new() };
+        let mut entries: Vec<HistoryEntry> = dirs
+            .flatten()
+            .filter_map(|d| read_json(&d.path().join(META_FILE)).ok())
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.started_at));
+        entries
+    }
+
+    #[cfg(any(feature = "gui", test))]
+    pub fn load(&self, run_id: &str) -> Result<HistoryRun, String> {
+        let dir = self.run_dir(run_id)?;
+        let entry = read_json(&dir.join(META_FILE))?;
+        let input = std::fs::read_to_string(dir.join(INPUT_FILE))
+            .map_err(|e| format!("Failed to read stored input: {}", e))?;
+        let has_output = dir.join(OUTPUT_FILE).is_file();
+        let logs = read_json(&dir.join(LOGS_FILE)).ok();
+        Ok(HistoryRun { entry, input, has_output, logs })
+    }
+
+    /// Parse a stored run's output; see `EngineResult::from_reader` for `spill_dir`.
+    pub fn load_result(&self, run_id: &str, spill_dir: &Path) -> Result<EngineResult, String> {
+        let path = self.run_dir(run_id)?.join(OUTPUT_FILE);
+        let file = File::open(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        EngineResult::from_reader(BufReader::new(file), Some(spill_dir))
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// The newest completed run stored under `cache_key`, as the outcome
+    /// `run_engine` would have produced. Partial output is never reused: the
+    /// engine stopped short, and running again may get further.
+    pub fn find_cached(&self, cache_key: &str, spill_dir: &Path) -> Option<RunOutcome> {
+        let entry = self.list().into_iter().find(|e| {
+            e.state == RunState::Completed && !e.partial && e.cache_key.as_deref() == Some(cache_key)
+        })?;
+        let result = self.load_result(&entry.run_id, spill_dir).ok()?;
+        let logs = read_json(&self.dir.join(&entry.run_id).join(LOGS_FILE)).ok();
+        Some(RunOutcome::Completed {
+            result: Arc::new(result),
+            partial: false,
+            max_residual: entry.max_residual,
+            summary: entry.summary,
+            logs: logs.unwrap_or_default(),
+            cached_run_id: Some(entry.run_id),
+            attempts: entry.attempts,
+        })
+    }
+
+    /// Final node pressures of the newest converged run stored under
+    /// `model_key`, with that run's ID.
+    pub fn latest_pressures(&self, model_key: &str) -> Option<(String, BTreeMap<i32, f64>)> {
+        self.list().into_iter().filter(|e| e.model_key.as_deref() == Some(model_key)).find_map(|entry| {
+            let pressures = read_json(&self.dir.join(&entry.run_id).join(PRESSURES_FILE)).ok()?;
+            Some((entry.run_id, pressures))
+        })
+    }
+
+    /// Replace a run's notes and/or tags; `None` leaves that field unchanged.
+    #[cfg(feature = "gui")]
+    pub fn annotate(
+        &self,
+        run_id: &str,
+        notes: Option<String>,
+        tags: Option<Vec<String>>,
+    ) -> Result<HistoryEntry, String> {
+        let _guard = self.write.lock().unwrap();
+        let path = self.run_dir(run_id)?.join(META_FILE);
+        let mut entry: HistoryEntry = read_json(&path)?;
+        if let Some(notes) = notes {
+            entry.notes = notes;
+        }
+        if let Some(tags) = tags {
+            entry.tags = tags;
+        }
+        write_file(&path, &to_json(&entry)?)?;
+        Ok(entry)
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn delete(&self, run_id: &str) -> Result<(), String> {
+        let _guard = self.write.lock().unwrap();
+        std::fs::remove_dir_all(self.run_dir(run_id)?).map_err(|e| format!("Failed to delete run: {}", e))
+    }
+
+    /// Delete the oldest runs until the history fits in `quota_bytes`.
+    /// Returns the IDs of the deleted runs.
+    pub fn prune(&self, quota_bytes: u64) -> Vec<String> {
+        let _guard = self.write.lock().unwrap();
+        let mut entries = self.list();
+        let mut total: u64 = entries.iter().map(|e| e.size_bytes).sum();
+        let mut removed = Vec::new();
+        while total > quota_bytes {
+            let Some(oldest) = entries.pop() else { break };
+            if std::fs::remove_dir_all(self.dir.join(&oldest.run_id)).is_ok() {
+                total = total.saturating_sub(oldest.size_bytes);
+                removed.push(oldest.run_id);
+            }
+        }
+        removed
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize history: {}", e))
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Stream a result to disk; transient results can be larger than memory.
+fn write_output(path: &Path, result: &EngineResult) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, result)
+        .map_err(|e| e.to_string())
+        .and_then(|()| writer.flush().map_err(|e| e.to_string()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    std::fs::read_dir(dir)
+        .map(|files| files.flatten().filter_map(|f| f.metadata().ok()).map(|m| m.len()).sum())
+        .unwrap_or(0)
+}
@@ -0,0 +1,384 @@
+//! Queueing an engine run: the body of `run_engine`, free of Tauri types so
+//! it can be exercised against a mock engine.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::cache;
+use crate::engine::RunOutcome;
+use crate::error::EngineError;
+use crate::history::{HistoryEntry, HistoryStore};
+use crate::limits::RunLimits;
+use crate::options::RunOptions;
+use crate::progress::Progress;
+use crate::retry::{self, RetryPolicy};
+use crate::runner::{EngineRunner, ProgressCallback};
+use crate::runs::{self, Job, JobManager};
+use crate::segments::{self, FinishedSegment, Plan, SegmentPolicy, Window};
+use crate::validation;
+use crate::warm_start;
+use crate::workdir::WorkDir;
+
+/// Called with the run ID for every progress update, after it is recorded on the job.
+pub type ProgressHook = Arc<dyn Fn(&str, Progress) + Send + Sync>;
+
+/// Called with the run ID as each segment of a segmented run finishes.
+pub type SegmentHook = Arc<dyn Fn(&str, &FinishedSegment) + Send + Sync>;
+
+pub struct Launcher {
+    pub jobs: JobManager,
+    pub workdir: WorkDir,
+    pub history: HistoryStore,
+    pub on_progress: ProgressHook,
+    pub on_segment: SegmentHook,
+}
+
+pub struct RunRequest {
+    pub input: String,
+    pub options: RunOptions,
+    pub limits: RunLimits,
+    /// Run the engine even if an identical run is in the history
+    pub fresh: bool,
+    /// Record the run and prune the history to this size [bytes]; `None` keeps no history
+    pub history_quota: Option<u64>,
+    /// Solve again with the other method if the solve does not converge
+    pub retry: Option<RetryPolicy>,
+    /// Start from the pressures of the latest converged run of the same model
+    pub warm_start: bool,
+    /// Split a transient run into segments, checkpointed as they finish
+    pub segments: Option<SegmentPolicy>,
+}
+
+impl Launcher {
+    /// Queue `request` on `runner` and return the new run's ID. See `run_engine`.
+    pub fn launch(&self, runner: Arc<dyn EngineRunner>, request: RunRequest) -> Result<String, String> {
+        let RunRequest { input, options, limits, fresh, history_quota, retry, warm_start, segments } = request;
+        let info = runner.info();
+        options.validate(&info)?;
+
+        let plan = segments.and_then(|policy| Plan::new(&input, policy));
+        let plan = match plan {
+            Some(_) if !info.at_least(segments::MIN_ENGINE_VERSION) => {
+                log::warn!("Engine cannot seed initialConcentrations; running the model in one piece");
+                None
+            }
+            plan => plan,
+        };
+
+        let retry = retry.map(|policy| match policy {
+            RetryPolicy { relax_transient: true } if !info.at_least(retry::MIN_RELAX_ENGINE_VERSION) => {
+                log::warn!("Engine cannot relax the transient airflow solve; retrying with -m alone");
+                RetryPolicy { relax_transient: false }
+            }
+            policy => policy,
+        });
+
+        // C-06: Use UUID to avoid temp file collisions from concurrent runs
+        let run_id = uuid::Uuid::new_v4().to_string();
+
+        // Stitched, retried and warm-started runs do not solve exactly the
+        // input given, so each combination is kept apart
+        let cache_key = cache::cache_key(&input, &options, info.version.as_deref()).map(|mut key| {
+            if let Some(plan) = &plan {
+                key.push_str(&format!("-seg{}", plan.length));
+            }
+            match retry {
+                Some(RetryPolicy { relax_transient: true }) => key.push_str("-retry-relaxed"),
+                Some(_) => key.push_str("-retry"),
+                None => {}
+            }
+            if warm_start {
+                key.push_str("-warm");
+            }
+            key
+        });
+        let checkpoints = plan.as_ref().and(cache_key.as_deref()).map(|key| self.workdir.checkpoints(key));
+        if fresh {
+            if let Some(dir) = &checkpoints {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+        } else {
+            let cached = cache_key.as_deref().and_then(|key| self.history.find_cached(key, self.workdir.path()));
+            if let Some(outcome) = cached {
+                log::info!("Run {} reuses the stored result of an identical run", run_id);
+                self.jobs.complete(&run_id, outcome);
+                return Ok(run_id);
+            }
+        }
+
+        let model_key = cache::model_key(&input);
+        let workdir = self.workdir.clone();
+        let id = run_id.clone();
+        let jobs = self.jobs.clone();
+        let history = self.history.clone();
+        let report = self.on_progress.clone();
+        let on_segment = self.on_segment.clone();
+        let job: Job = Box::new(move |cancel| {
+            if let Err(errors) = validation::validate_input(&input) {
+                let error = EngineError::InvalidInput { errors, logs: Default::default() };
+                return RunOutcome::Failed { error };
+            }
+            let on_progress = |window: Option<Window>| -> ProgressCallback {
+                let (jobs, report, id) = (jobs.clone(), report.clone(), id.clone());
+                Box::new(move |progress: Progress| {
+                    let progress = match window {
+                        Some(window) => window.progress(progress),
+                        None => progress,
+                    };
+                    jobs.set_progress(&id, progress.clone());
+                    report(&id, progress);
+                })
+            };
+            let seeded = match (warm_start, &model_key) {
+                (true, Some(key)) => seed_pressures(&history, key, &input),
+                _ => None,
+            };
+            let engine_input = seeded.as_ref().map_or(input.as_str(), |(input, _)| input);
+
+            let started_at = runs::now_ms();
+            let started = Instant::now();
+            let run = |name: &str, input: &str, window: Option<Window>| match retry {
+                None => runner.run(workdir.files(name), input, &options, limits, cancel, on_progress(window)),
+                Some(policy) => retry::run_with_retry(policy, input, &options, |attempt, input, options| {
+                    let files = workdir.files(&format!("{}-{}", name, attempt));
+                    runner.run(files, input, options, limits, cancel, on_progress(window))
+                }),
+            };
+            let outcome = match &plan {
+                None => run(&id, engine_input, None),
+                Some(plan) => {
+                    let window = plan.window();
+                    segments::run_segmented(
+                        plan,
+                        engine_input,
+                        checkpoints.as_deref(),
+                        workdir.path(),
+                        |index, input| run(&format!("{}-s{}", id, index), input, Some(window)),
+                        |segment| on_segment(&id, segment),
+                    )
+                }
+            };
+
+            if let Some(quota) = history_quota {
+                let mut entry = HistoryEntry::new(&id, started_at, started.elapsed(), &outcome, info.version, options);
+                entry.cache_key = cache_key;
+                entry.model_key = model_key;
+                entry.warm_start_run_id = seeded.map(|(_, source)| source);
+                match history.record(entry, &input, &outcome) {
+                    Ok(()) => {
+                        history.prune(quota);
+                    }
+                    Err(e) => log::warn!("Failed to record run {} in history: {}", id, e),
+                }
+            }
+            outcome
+        });
+        self.jobs.submit(&run_id, job);
+
+        Ok(run_id)
+    }
+}
+
+/// The input seeded with the pressures of the latest converged run of the
+/// model, and that run's ID.
+fn seed_pressures(history: &HistoryStore, model_key: &str, input: &str) -> Option<(String, String)> {
+    let (source, pressures) = history.latest_pressures(model_key)?;
+    let (seeded, count) = warm_start::inject_pressures(input, &pressures)?;
+    log::info!("Warm start from run {}: initial pressures at {} nodes", source, count);
+    Some((seeded, source))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::options::SolverMethod;
+    use crate::runner::MockRunner;
+    use crate::summary::RunSummary;
+    #[cfg(unix)]
+    use crate::testing::{copy_case_output, fake_engine};
+    use crate::testing::{assert_case_output, assert_failed, case_input, launcher, request, run, TestDir, CASES};
+
+    #[test]
+    fn mock_runs_return_validation_outputs() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        for case in CASES {
+            let outcome = run(&launcher, MockRunner::case(case), request(case_input(case)));
+            assert_case_output(&outcome, case, false);
+        }
+    }
+
+    #[test]
+    fn exit_code_1_is_an_input_error() {
+        let dir = TestDir::new();
+        let outcome = run(&launcher(&dir), MockRunner::exiting(1), request(case_input("case01_3room")));
+        assert_failed(&outcome, |e| matches!(e, EngineError::InputError { logs } if logs.exit_code == Some(1)));
+    }
+
+    #[test]
+    fn exit_code_2_keeps_output_as_partial() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let runner = MockRunner { exit_code: 2, ..MockRunner::case("case01_3room") };
+        assert_case_output(&run(&launcher, runner, request(case_input("case01_3room"))), "case01_3room", true);
+
+        let outcome = run(&launcher, MockRunner::exiting(2), request(case_input("case01_3room")));
+        assert_failed(&outcome, |e| matches!(e, EngineError::NotConverged { .. }));
+    }
+
+    #[test]
+    fn missing_output_fails() {
+        let dir = TestDir::new();
+        let outcome = run(&launcher(&dir), MockRunner::exiting(0), request(case_input("case01_3room")));
+        assert_failed(&outcome, |e| matches!(e, EngineError::OutputMissing { .. }));
+    }
+
+    #[test]
+    fn slow_runs_time_out() {
+        let dir = TestDir::new();
+        let runner = MockRunner { duration: Duration::from_secs(10), ..MockRunner::case("case01_3room") };
+        let mut request = request(case_input("case01_3room"));
+        request.limits.timeout_secs = Some(1);
+        let outcome = run(&launcher(&dir), runner, request);
+        assert_failed(&outcome, |e| matches!(e, EngineError::TimedOut { timeout_secs: 1, .. }));
+    }
+
+    #[test]
+    fn invalid_input_never_reaches_the_engine() {
+        let dir = TestDir::new();
+        let outcome = run(&launcher(&dir), MockRunner::case("case01_3room"), request(r#"{"nodes": 1}"#.to_string()));
+        assert_failed(&outcome, |e| matches!(e, EngineError::InvalidInput { errors, .. } if !errors.is_empty()));
+    }
+
+    #[test]
+    fn progress_is_recorded_on_the_job() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let stdout = "Running transient simulation: 0s to 100s (dt=10s)...\n\r  t=100/100s\n";
+        let runner = MockRunner { stdout: stdout.to_string(), ..MockRunner::case("case02_co2_source") };
+        let run_id = launcher.launch(Arc::new(runner), request(case_input("case02_co2_source"))).unwrap();
+        launcher.jobs.wait(&run_id, Some(Duration::from_secs(30))).unwrap().unwrap();
+        let progress = launcher.jobs.status(&run_id).unwrap().progress.expect("no progress recorded");
+        assert_eq!(progress.percent, 100.0);
+    }
+
+    #[test]
+    fn summary_is_attached_and_recorded() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let stdout = "Network: 3 nodes, 3 links\nUnknown pressures: 2\n\
+                      Solving steady-state with Sub-Relaxation method...\n\
+                      Converged in 42 iterations (max residual: 3.5e-07 kg/s)\n";
+        let runner = MockRunner { stdout: stdout.to_string(), ..MockRunner::case("case01_3room") };
+        let request = RunRequest { history_quota: Some(u64::MAX), ..request(case_input("case01_3room")) };
+        let outcome = run(&launcher, runner, request);
+        let RunOutcome::Completed { summary, .. } = &outcome else { panic!("{:?}", outcome) };
+        assert_eq!(*summary, RunSummary::parse(stdout));
+        assert_eq!((summary.method, summary.iterations), (Some(SolverMethod::SubRelaxation), Some(42)));
+        assert_eq!(launcher.history.list()[0].summary, *summary);
+    }
+
+    #[test]
+    fn identical_runs_are_served_from_history() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let input = case_input("case01_3room");
+        let first = launcher
+            .launch(
+                Arc::new(MockRunner::case("case01_3room")),
+                RunRequest { history_quota: Some(u64::MAX), ..request(input.clone()) },
+            )
+            .unwrap();
+        launcher.jobs.wait(&first, Some(Duration::from_secs(30))).unwrap().unwrap();
+
+        // Would fail if it were run
+        let outcome = run(&launcher, MockRunner::exiting(1), request(input.clone()));
+        let RunOutcome::Completed { cached_run_id, .. } = &outcome else { panic!("{:?}", outcome) };
+        assert_eq!(cached_run_id.as_deref(), Some(first.as_str()));
+        assert_case_output(&outcome, "case01_3room", false);
+
+        let outcome = run(&launcher, MockRunner::exiting(1), RunRequest { fresh: true, ..request(input) });
+        assert_failed(&outcome, |e| matches!(e, EngineError::InputError { .. }));
+    }
+
+    #[test]
+    fn history_serves_only_whole_runs_with_the_same_retry() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let input = case_input("case01_3room");
+        let recorded = |retry| RunRequest { retry, history_quota: Some(u64::MAX), ..request(input.clone()) };
+
+        let partial = run(&launcher, MockRunner { exit_code: 2, ..MockRunner::case("case01_3room") }, recorded(None));
+        let RunOutcome::Completed { partial: true, .. } = &partial else { panic!("{:?}", partial) };
+        let outcome = run(&launcher, MockRunner::case("case01_3room"), request(input.clone()));
+        let RunOutcome::Completed { cached_run_id, .. } = &outcome else { panic!("{:?}", outcome) };
+        assert_eq!(*cached_run_id, None, "partial output is run again");
+
+        let policy = RetryPolicy { relax_transient: true };
+        let retried = run(&launcher, MockRunner::case("case01_3room"), recorded(Some(policy)));
+        let RunOutcome::Completed { cached_run_id: None, .. } = &retried else { panic!("{:?}", retried) };
+        // Would fail if it were run
+        let same = RunRequest { retry: Some(policy), ..request(input.clone()) };
+        let outcome = run(&launcher, MockRunner::exiting(1), same);
+        let RunOutcome::Completed { cached_run_id: Some(_), .. } = &outcome else { panic!("{:?}", outcome) };
+        let other = RunRequest { retry: Some(RetryPolicy::default()), ..request(input) };
+        let outcome = run(&launcher, MockRunner::exiting(1), other);
+        assert_failed(&outcome, |e| matches!(e, EngineError::InputError { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn process_runs_read_engine_output() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        for case in CASES {
+            let runner = fake_engine(&dir.0, &copy_case_output(case));
+            assert_case_output(&run(&launcher, runner, request(case_input(case))), case, false);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn process_exit_codes() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let input = || request(case_input("case01_3room"));
+
+        let outcome = run(&launcher, fake_engine(&dir.0, "echo 'Error: bad input' >&2\nexit 1\n"), input());
+        assert_failed(&outcome, |e| matches!(e, EngineError::InputError { logs } if logs.stderr.contains("bad input")));
+
+        let script = format!("{}exit 2\n", copy_case_output("case01_3room"));
+        assert_case_output(&run(&launcher, fake_engine(&dir.0, &script), input()), "case01_3room", true);
+
+        let outcome = run(&launcher, fake_engine(&dir.0, "exit 2\n"), input());
+        assert_failed(&outcome, |e| matches!(e, EngineError::NotConverged { .. }));
+
+        let outcome = run(&launcher, fake_engine(&dir.0, "exit 0\n"), input());
+        assert_failed(&outcome, |e| matches!(e, EngineError::OutputMissing { .. }));
+
+        let outcome = run(&launcher, fake_engine(&dir.0, "echo '{' > \"$4\"\n"), input());
+        assert_failed(&outcome, |e| matches!(e, EngineError::InvalidOutput { .. }));
+
+        // A capped engine that dies from a signal without running out of memory
+        let limits = RunLimits { memory_limit_mb: Some(8192), ..RunLimits::default() };
+        let capped = || RunRequest { limits, ..input() };
+        for signal in ["SEGV", "ABRT", "KILL"] {
+            let outcome = run(&launcher, fake_engine(&dir.0, &format!("kill -{} $$\n", signal)), capped());
+            assert_failed(&outcome, |e| matches!(e, EngineError::Crashed { .. }));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn process_runs_time_out() {
+        let dir = TestDir::new();
+        let mut request = request(case_input("case01_3room"));
+        request.limits.timeout_secs = Some(1);
+        // `exec` so the timeout kills the sleeping process itself, closing its pipes
+        let outcome = run(&launcher(&dir), fake_engine(&dir.0, "exec sleep 30\n"), request);
+        assert_failed(&outcome, |e| matches!(e, EngineError::TimedOut { timeout_secs: 1, .. }));
+    }
+}
@@ -0,0 +1,183 @@
+//! Per-run resource limits for the engine process.
+//!
+//! The wall-clock timeout is enforced by the supervising thread on every
+//! platform. On Linux the engine additionally runs under `RLIMIT_AS` (memory)
+//! and `RLIMIT_CPU` (a backstop in case the app itself dies mid-run).
+
+use std::process::{Child, Command, ExitStatus};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Limits requested for a run. Unset fields fall back to the user's settings;
+/// a value of 0 disables that limit.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunLimits {
+    pub timeout_secs: Option<u64>,
+    pub memory_limit_mb: Option<u64>,
+}
+
+impl RunLimits {
+    /// Fill any unset field from `defaults`.
+    #[cfg(any(feature = "gui", test))]
+    pub fn or(self, defaults: RunLimits) -> RunLimits {
+        RunLimits {
+            timeout_secs: self.timeout_secs.or(defaults.timeout_secs),
+            memory_limit_mb: self.memory_limit_mb.or(defaults.memory_limit_mb),
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_secs.filter(|&s| s > 0).map(Duration::from_secs)
+    }
+
+    pub fn memory_bytes(&self) -> Option<u64> {
+        self.memory_limit_mb.filter(|&mb| mb > 0).map(|mb| mb * 1024 * 1024)
+    }
+
+    /// Install the limits on `cmd` so they apply to the spawned child only.
+    #[cfg(target_os = "linux")]
+    pub fn apply(&self, cmd: &mut Command) {
+        use std::os::unix::process::CommandExt;
+
+        let memory = self.memory_bytes();
+        // Give the CPU limit some headroom over the wall-clock timeout so the
+        // supervising thread normally gets to report the timeout itself
+        let cpu = self.timeout().map(|t| t.as_secs() + 60);
+
+        // SAFETY: the closure only calls the async-signal-safe `setrlimit`
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(bytes) = memory {
+                    if libc::setrlimit(libc::RLIMIT_AS, &rlimit(bytes)) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(secs) = cpu {
+                    if libc::setrlimit(libc::RLIMIT_CPU, &rlimit(secs)) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn apply(&self, _cmd: &mut Command) {}
+
+    /// Whether a failed exit shows the engine ran out of memory: either it
+    /// reported `std::bad_alloc`, or it was killed or aborted with its peak
+    /// resident set `peak_rss` [bytes] close to the cap. Other signals are
+    /// crashes, even with a cap set.
+    pub fn is_out_of_memory(&self, status: &ExitStatus, stderr: &str, peak_rss: Option<u64>) -> bool {
+        let near_cap = match (self.memory_bytes(), peak_rss) {
+            (Some(cap), Some(peak)) => peak as f64 >= NEAR_CAP * cap as f64,
+            _ => false,
+        };
+        stderr.contains("bad_alloc") || (near_cap && killed_by_allocation_failure(status))
+    }
+}
+
+/// Fraction of the memory cap a killed engine must have reached to count as
+/// out of memory
+const NEAR_CAP: f64 = 0.9;
+
+/// `Child::try_wait` that also returns the child's peak resident set
+/// [bytes]. The child is reaped, so `Child::wait` must not be called after
+/// it has returned a status.
+#[cfg(target_os = "linux")]
+pub fn try_wait(child: &mut Child) -> std::io::Result<Option<(ExitStatus, Option<u64>)>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut status = 0;
+    // SAFETY: `rusage` is plain data, for which all zeroes is valid
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    // SAFETY: `wait4` only writes to the two locals
+    let pid = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, libc::WNOHANG, &mut usage) };
+    match pid {
+        0 => Ok(None),
+        -1 => Err(std::io::Error::last_os_error()),
+        // `ru_maxrss` is in KiB on Linux
+        _ => Ok(Some((ExitStatus::from_raw(status), Some(usage.ru_maxrss as u64 * 1024)))),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn try_wait(child: &mut Child) -> std::io::Result<Option<(ExitStatus, Option<u64>)>> {
+    Ok(child.try_wait()?.map(|status| (status, None)))
+}
+
+/// Whether the kernel stopped the engine for exceeding `RLIMIT_CPU`.
+#[cfg(target_os = "linux")]
+pub fn hit_cpu_limit(status: &ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal() == Some(libc::SIGXCPU)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn hit_cpu_limit(_status: &ExitStatus) -> bool {
+    false
+}
+
+/// Killed by the kernel's OOM killer, or aborted by an uncaught allocation
+/// failure.
+#[cfg(target_os = "linux")]
+fn killed_by_allocation_failure(status: &ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    matches!(status.signal(), Some(libc::SIGABRT | libc::SIGKILL))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn killed_by_allocation_failure(_status: &ExitStatus) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn rlimit(value: u64) -> libc::rlimit {
+    libc::rlimit { rlim_cur: value as libc::rlim_t, rlim_max: value as libc::rlim_t }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+
+    use super::*;
+
+    const CAP_MB: u64 = 1024;
+
+    fn limits() -> RunLimits {
+        RunLimits { timeout_secs: None, memory_limit_mb: Some(CAP_MB) }
+    }
+
+    fn signalled(signal: i32) -> ExitStatus {
+        ExitStatus::from_raw(signal)
+    }
+
+    #[test]
+    fn segfaults_are_crashes_even_near_the_cap() {
+        let peak = Some(CAP_MB * 1024 * 1024);
+        assert!(!limits().is_out_of_memory(&signalled(libc::SIGSEGV), "", peak));
+    }
+
+    #[test]
+    fn aborts_need_bad_alloc_or_a_peak_near_the_cap() {
+        let abort = signalled(libc::SIGABRT);
+        let assertion = "contam_engine: Solver.cpp:120: Assertion `n > 0' failed.";
+        assert!(!limits().is_out_of_memory(&abort, assertion, Some(10 * 1024 * 1024)));
+        assert!(!limits().is_out_of_memory(&abort, assertion, None));
+        let bad_alloc = "terminate called after throwing an instance of 'std::bad_alloc'";
+        assert!(limits().is_out_of_memory(&abort, bad_alloc, None));
+        assert!(limits().is_out_of_memory(&abort, "", Some(CAP_MB * 1024 * 1000)));
+    }
+
+    #[test]
+    fn external_kills_are_crashes() {
+        let kill = signalled(libc::SIGKILL);
+        assert!(!limits().is_out_of_memory(&kill, "", Some(50 * 1024 * 1024)));
+        assert!(limits().is_out_of_memory(&kill, "", Some(CAP_MB * 1024 * 1024)));
+        let uncapped = RunLimits { timeout_secs: None, memory_limit_mb: Some(0) };
+        assert!(!uncapped.is_out_of_memory(&kill, "", Some(CAP_MB * 1024 * 1024)));
+    }
+}
@@ -0,0 +1,240 @@
+//! Model checks beyond the schema: references between parts of the model,
+//! which `JsonReader` resolves without checking, and settings the engine
+//! accepts but that are most likely mistakes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::topology::{LinkElement, NodeType, Topology};
+
+/// Zone temperatures outside this range are probably in °C [K]
+const TEMPERATURE_RANGE: (f64, f64) = (200.0, 400.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// The engine will fail or read the model wrongly
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub severity: Severity,
+    /// JSON pointer to the offending value, as on `SchemaViolation`
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.pointer, self.message)
+    }
+}
+
+#[derive(Default)]
+struct Findings(Vec<Finding>);
+
+impl Findings {
+    fn error(&mut self, pointer: String, message: String) {
+        self.0.push(Finding { severity: Severity::Error, pointer, message });
+    }
+
+    fn warning(&mut self, pointer: String, message: String) {
+        self.0.push(Finding { severity: Severity::Warning, pointer, message });
+    }
+}
+
+/// Check a model that passed schema validation. Errors come first.
+pub fn lint(model: &Topology) -> Vec<Finding> {
+    let mut findings = Findings::default();
+    let nodes = unique_ids(&mut findings, "nodes", model.nodes.iter().map(|n| n.id));
+    unique_ids(&mut findings, "links", model.links.iter().map(|l| l.id));
+    let species = unique_ids(&mut findings, "species", model.species.iter().map(|s| s.id));
+    let schedules = unique_ids(&mut findings, "schedules", model.schedules.iter().map(|s| s.id));
+
+    if !model.nodes.iter().any(|n| n.node_type == NodeType::Ambient) {
+        findings.error("/nodes".to_string(), "no ambient node: the network has no known pressure".to_string());
+    }
+
+    let mut linked = BTreeSet::new();
+    for (i, link) in model.links.iter().enumerate() {
+        for (end, id) in [("from", link.from), ("to", link.to)] {
+            if nodes.contains(&id) {
+                linked.insert(id);
+            } else {
+                findings.error(format!("/links/{}/{}", i, end), format!("unknown node {}", id));
+            }
+        }
+        if link.from == link.to {
+            findings.error(format!("/links/{}", i), format!("link {} connects node {} to itself", link.id, link.from));
+        }
+        if let Some(LinkElement::Reference(name)) = &link.element {
+            if !model.flow_elements.contains_key(name) {
+                findings.error(format!("/links/{}/element", i), format!("unknown flow element '{}'", name));
+            }
+        }
+        if let Some(id) = link.schedule_id.filter(|&id| id >= 0 && !schedules.contains(&id)) {
+            findings.error(format!("/links/{}/scheduleId", i), format!("unknown schedule {}", id));
+        }
+    }
+
+    let transient = model.transient.is_some() || !model.species.is_empty();
+    for (i, node) in model.nodes.iter().enumerate() {
+        let name = node.name.as_deref().unwrap_or("");
+        if !linked.contains(&node.id) && !model.links.is_empty() {
+            findings.warning(format!("/nodes/{}", i), format!("node {} '{}' has no links", node.id, name));
+        }
+        if node.node_type == NodeType::Ambient {
+            continue;
+        }
+        if let Some(t) = node.temperature.filter(|t| !(TEMPERATURE_RANGE.0..=TEMPERATURE_RANGE.1).contains(t)) {
+            findings.warning(format!("/nodes/{}/temperature", i), format!("{} K is outside 200-400 K", t));
+        }
+        if transient && node.node_type == NodeType::Normal && node.volume.map_or(true, |v| v <= 0.0) {
+            findings.warning(format!("/nodes/{}/volume", i), format!("node {} '{}' has no volume", node.id, name));
+        }
+        for species_id in node.initial_concentrations.keys() {
+            if !species_id.parse::<i32>().is_ok_and(|id| species.contains(&id)) {
+                let pointer = format!("/nodes/{}/initialConcentrations/{}", i, species_id);
+                findings.error(pointer, format!("unknown species {}", species_id));
+            }
+        }
+    }
+
+    for (i, source) in model.sources.iter().enumerate() {
+        if !nodes.contains(&source.zone_id) {
+            findings.error(format!("/sources/{}/zoneId", i), format!("unknown node {}", source.zone_id));
+        }
+        if !species.contains(&source.species_id) {
+            findings.error(format!("/sources/{}/speciesId", i), format!("unknown species {}", source.species_id));
+        }
+        if let Some(id) = source.schedule_id.filter(|&id| id >= 0 && !schedules.contains(&id)) {
+            findings.error(format!("/sources/{}/scheduleId", i), format!("unknown schedule {}", id));
+        }
+    }
+    for (i, zone) in model.zone_temperature_schedules.iter().enumerate() {
+        if !nodes.contains(&zone.node_id) {
+            findings.error(format!("/zoneTemperatureSchedules/{}/nodeId", i), format!("unknown node {}", zone.node_id));
+        }
+        if !schedules.contains(&zone.schedule_id) {
+            let pointer = format!("/zoneTemperatureSchedules/{}/scheduleId", i);
+            findings.error(pointer, format!("unknown schedule {}", zone.schedule_id));
+        }
+    }
+
+    match &model.transient {
+        Some(config) => {
+            // `JsonReader`'s defaults
+            let start = config.start_time.unwrap_or(0.0);
+            let end = config.end_time.unwrap_or(3600.0);
+            let step = config.time_step.unwrap_or(60.0);
+            let interval = config.output_interval.unwrap_or(60.0);
+            if end <= start {
+                findings.error("/transient/endTime".to_string(), format!("ends at {} s, before it starts", end));
+            }
+            if step <= 0.0 {
+                findings.error("/transient/timeStep".to_string(), "must be positive".to_string());
+            }
+            if interval <= 0.0 {
+                findings.error("/transient/outputInterval".to_string(), "must be positive".to_string());
+            } else if step > 0.0 && ((interval / step).round() * step - interval).abs() > 1e-9 * interval {
+                let message = format!("{} s is not a multiple of the {} s time step", interval, step);
+                findings.warning("/transient/outputInterval".to_string(), message);
+            }
+        }
+        None if !model.species.is_empty() => {
+            let message = "species without a transient section run 0-3600 s in 60 s steps".to_string();
+            findings.warning("/transient".to_string(), message);
+        }
+        None => {}
+    }
+
+    let mut findings = findings.0;
+    findings.sort_by_key(|f| f.severity);
+    findings
+}
+
+/// The IDs of one collection, reporting any that repeat.
+fn unique_ids(findings: &mut Findings, collection: &str, ids: impl Iterator<Item = i32>) -> BTreeSet<i32> {
+    let mut seen = BTreeMap::new();
+    for (i, id) in ids.enumerate() {
+        if let Some(first) = seen.insert(id, i) {
+            let message = format!("ID {} is already used by /{}/{}", id, collection, first);
+            findings.error(format!("/{}/{}/id", collection, i), message);
+        }
+    }
+    seen.into_keys().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::testing::{case_input, CASES};
+
+    fn lint_value(model: &Value) -> Vec<Finding> {
+        lint(&serde_json::from_value(model.clone()).unwrap())
+    }
+
+    fn pointers(findings: &[Finding], severity: Severity) -> Vec<&str> {
+        findings.iter().filter(|f| f.severity == severity).map(|f| f.pointer.as_str()).collect()
+    }
+
+    #[test]
+    fn validation_cases_have_no_errors() {
+        for case in CASES {
+            let findings = lint_value(&serde_json::from_str(&case_input(case)).unwrap());
+            assert!(pointers(&findings, Severity::Error).is_empty(), "{}: {:?}", case, findings);
+        }
+    }
+
+    #[test]
+    fn dangling_references_and_duplicate_ids_are_errors() {
+        let mut model: Value = serde_json::from_str(&case_input("case01_3room")).unwrap();
+        model["links"][0]["to"] = 99.into();
+        model["links"][1]["id"] = model["links"][0]["id"].clone();
+        model["links"][2]["element"] = "missing".into();
+        let findings = lint_value(&model);
+        assert_eq!(pointers(&findings, Severity::Error), ["/links/1/id", "/links/0/to", "/links/2/element"]);
+        // Errors are listed before warnings
+        assert!(findings.windows(2).all(|pair| pair[0].severity <= pair[1].severity));
+    }
+
+    #[test]
+    fn transient_settings_are_checked() {
+        let model = json!({
+            "nodes": [
+                {"id": 0, "name": "Out", "type": "ambient"},
+                {"id": 1, "name": "Room", "temperature": 20.0}
+            ],
+            "links": [{"id": 1, "from": 0, "to": 1, "elevation": 1.0}],
+            "species": [{"id": 1, "name": "CO2"}],
+            "transient": {"startTime": 600, "endTime": 600, "timeStep": 60, "outputInterval": 90}
+        });
+        let findings = lint_value(&model);
+        assert_eq!(pointers(&findings, Severity::Error), ["/transient/endTime"]);
+        assert_eq!(
+            pointers(&findings, Severity::Warning),
+            ["/nodes/1/temperature", "/nodes/1/volume", "/transient/outputInterval"]
+        );
+        assert_eq!(findings[0].to_string(), "error: /transient/endTime: ends at 600 s, before it starts");
+    }
+
+    #[test]
+    fn models_without_an_ambient_node_are_errors() {
+        let model = json!({
+            "nodes": [{"id": 1, "name": "A"}, {"id": 2, "name": "B"}],
+            "links": [{"id": 1, "from": 1, "to": 2, "elevation": 1.0}]
+        });
+        assert_eq!(pointers(&lint_value(&model), Severity::Error), ["/nodes"]);
+    }
+}
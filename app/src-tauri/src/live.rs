@@ -0,0 +1,314 @@
+//! Live mode: steady models re-solved in the background while they are
+//! edited, so overlays follow the edits.
+//!
+//! The frontend pushes every edit with `update`. A solve starts once edits
+//! have paused for the debounce interval; any live solve still running is
+//! cancelled first, since its result would be stale. Each solve starts from
+//! the pressures of the previous live solve. Live solves are not recorded in
+//! the history.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::engine::RunOutcome;
+use crate::error::EngineError;
+use crate::launch::{Launcher, RunRequest};
+use crate::limits::RunLimits;
+use crate::options::RunOptions;
+use crate::results::{EngineResult, SteadyResult};
+use crate::runner::EngineRunner;
+use crate::warm_start;
+
+/// Quiet time after an edit before it is solved.
+#[cfg(feature = "gui")]
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Resolves the engine at the start of each solve, so settings changes apply.
+pub type RunnerFactory = Box<dyn Fn() -> Arc<dyn EngineRunner> + Send + Sync>;
+
+/// Called with every live solve that was not superseded.
+pub type LiveHook = Box<dyn Fn(LiveUpdate) + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveUpdate {
+    /// The `update` call that was solved
+    pub revision: u64,
+    pub run_id: Option<String>,
+    #[serde(flatten)]
+    pub outcome: LiveOutcome,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum LiveOutcome {
+    /// `partial`: the solve did not converge and these are its last iterates
+    Solved { partial: bool, result: SteadyResult },
+    Failed { error: EngineError },
+}
+
+struct Edit {
+    revision: u64,
+    input: String,
+    options: RunOptions,
+    limits: RunLimits,
+    edited_at: Instant,
+}
+
+#[derive(Default)]
+struct State {
+    revision: u64,
+    /// Latest edit not yet solved
+    pending: Option<Edit>,
+    /// Run ID of the solve in flight
+    running: Option<String>,
+    /// Newest revision reported, so a late result never replaces a newer one.
+    /// Raised by `stop` so solves already started are not reported.
+    reported: u64,
+    /// Final pressures of the last converged live solve
+    pressures: BTreeMap<i32, f64>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+    launcher: Launcher,
+    runner: RunnerFactory,
+    debounce: Duration,
+    on_update: LiveHook,
+}
+
+/// Cheap to clone; all clones drive the same worker.
+#[derive(Clone)]
+pub struct LiveSolver {
+    shared: Arc<Shared>,
+}
+
+impl LiveSolver {
+    /// Start the worker thread. `launcher` should have a job manager of its
+    /// own, so live solves never wait behind queued runs.
+    pub fn new(launcher: Launcher, runner: RunnerFactory, debounce: Duration, on_update: LiveHook) -> Self {
+        let solver = Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State::default()),
+                changed: Condvar::new(),
+                launcher,
+                runner,
+                debounce,
+                on_update,
+            }),
+        };
+        let worker = solver.clone();
+        std::thread::spawn(move || worker.work());
+        solver
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap()
+    }
+
+    /// Queue an edited model to be solved once edits pause, replacing any
+    /// edit not yet solved. Returns the edit's revision, echoed on its update.
+    pub fn update(&self, input: String, options: RunOptions, limits: RunLimits) -> Result<u64, String> {
+        if !is_steady(&input) {
+            return Err("Live mode only solves steady models; this one has species or a transient section".to_string());
+        }
+        if options.hdf5_output.is_some() {
+            return Err("Live mode does not write HDF5 output".to_string());
+        }
+        let revision = {
+            let mut state = self.lock();
+            state.revision += 1;
+            let revision = state.revision;
+            state.pending = Some(Edit { revision, input, options, limits, edited_at: Instant::now() });
+            revision
+        };
+        self.shared.changed.notify_all();
+        Ok(revision)
+    }
+
+    /// Drop any pending edit and cancel the solve in flight.
+    #[cfg(feature = "gui")]
+    pub fn stop(&self) {
+        let running = {
+            let mut state = self.lock();
+            state.pending = None;
+            state.reported = state.revision;
+            state.pressures.clear();
+            state.running.take()
+        };
+        if let Some(run_id) = running {
+            self.shared.launcher.jobs.cancel(&run_id);
+        }
+    }
+
+    /// Wait for edits to pause, then solve the latest one.
+    fn work(&self) {
+        let mut state = self.lock();
+        loop {
+            let Some(edited_at) = state.pending.as_ref().map(|edit| edit.edited_at) else {
+                state = self.shared.changed.wait(state).unwrap();
+                continue;
+            };
+            let quiet = edited_at.elapsed();
+            if quiet < self.shared.debounce {
+                state = self.shared.changed.wait_timeout(state, self.shared.debounce - quiet).unwrap().0;
+                continue;
+            }
+            let edit = state.pending.take().expect("checked above");
+            let superseded = state.running.take();
+            let pressures = state.pressures.clone();
+            drop(state);
+
+            if let Some(run_id) = superseded {
+                self.shared.launcher.jobs.cancel(&run_id);
+            }
+            self.solve(edit, &pressures);
+            state = self.lock();
+        }
+    }
+
+    fn solve(&self, edit: Edit, pressures: &BTreeMap<i32, f64>) {
+        let Edit { revision, input, options, limits, .. } = edit;
+        let input = warm_start::inject_pressures(&input, pressures).map_or(input, |(seeded, _)| seeded);
+        let request = RunRequest {
+            input,
+            options,
+            limits,
+            fresh: true,
+            history_quota: None,
+            retry: None,
+            warm_start: false,
+            segments: None,
+        };
+        let run_id = match self.shared.launcher.launch((self.shared.runner)(), request) {
+            Ok(run_id) => run_id,
+            Err(message) => {
+                let outcome = LiveOutcome::Failed { error: EngineError::io(message) };
+                self.report(LiveUpdate { revision, run_id: None, outcome });
+                return;
+            }
+        };
+        self.lock().running = Some(run_id.clone());
+
+        let solver = self.clone();
+        std::thread::spawn(move || {
+            let outcome = match solver.shared.launcher.jobs.wait(&run_id, None) {
+                Ok(Some(outcome)) => outcome,
+                Ok(None) => return,
+                Err(message) => RunOutcome::Failed { error: EngineError::io(message) },
+            };
+            solver.finished(revision, run_id, outcome);
+        });
+    }
+
+    fn finished(&self, revision: u64, run_id: String, outcome: RunOutcome) {
+        {
+            let mut state = self.lock();
+            if state.running.as_ref() == Some(&run_id) {
+                state.running = None;
+            }
+            if let RunOutcome::Completed { result, partial: false, .. } = &outcome {
+                if result.converged() {
+                    state.pressures = result.final_pressures();
+                }
+            }
+        }
+        let outcome = match outcome {
+            RunOutcome::Completed { result, partial, .. } => match &*result {
+                EngineResult::Steady(steady) => LiveOutcome::Solved { partial, result: steady.clone() },
+                EngineResult::Transient(_) => {
+                    LiveOutcome::Failed { error: EngineError::io("Live solve produced transient output".to_string()) }
+                }
+            },
+            RunOutcome::Failed { error } => LiveOutcome::Failed { error },
+            RunOutcome::Cancelled => return,
+        };
+        self.report(LiveUpdate { revision, run_id: Some(run_id), outcome });
+    }
+
+    fn report(&self, update: LiveUpdate) {
+        {
+            let mut state = self.lock();
+            if update.revision <= state.reported {
+                return;
+            }
+            state.reported = update.revision;
+        }
+        (self.shared.on_update)(update);
+    }
+}
+
+/// Whether the engine will solve `input` as a steady model: it has neither
+/// species nor a `transient` section.
+fn is_steady(input: &str) -> bool {
+    let Ok(model) = serde_json::from_str::<serde_json::Value>(input) else {
+        // Left to input validation, which reports what is wrong
+        return true;
+    };
+    model.get("transient").map_or(true, |t| t.is_null())
+        && model.get("species").and_then(|s| s.as_array()).map_or(true, |s| s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::runner::MockRunner;
+    use crate::runs::RunState;
+    use crate::testing::{case_input, launcher, TestDir};
+
+    #[test]
+    fn live_edits_are_debounced_and_stale_solves_cancelled() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let jobs = launcher.jobs.clone();
+        let (updates, received) = mpsc::channel();
+        let runner = || MockRunner { duration: Duration::from_millis(500), ..MockRunner::case("case01_3room") };
+        let live = LiveSolver::new(
+            launcher,
+            Box::new(move || Arc::new(runner())),
+            Duration::from_millis(50),
+            Box::new(move |update| updates.send(update).unwrap()),
+        );
+        let input = case_input("case01_3room");
+        let edit = || live.update(input.clone(), RunOptions::default(), RunLimits::default()).unwrap();
+
+        for _ in 0..3 {
+            edit();
+        }
+        let started = Instant::now();
+        while jobs.list().is_empty() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(jobs.list().len(), 1, "a burst of edits is solved once");
+
+        // Supersedes the solve in flight
+        let latest = edit();
+        let update = received.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(update.revision, latest);
+        let LiveOutcome::Solved { partial, result } = update.outcome else { panic!("{:?}", update.outcome) };
+        assert!(!partial);
+        assert_eq!(result.nodes.len(), 3);
+        let states: Vec<RunState> = jobs.list().iter().map(|run| run.state).collect();
+        assert_eq!(states, [RunState::Cancelled, RunState::Completed]);
+        assert!(received.recv_timeout(Duration::from_millis(200)).is_err(), "the cancelled solve is not reported");
+    }
+
+    #[test]
+    fn live_mode_only_takes_steady_models() {
+        let dir = TestDir::new();
+        let live = LiveSolver::new(
+            launcher(&dir),
+            Box::new(|| Arc::new(MockRunner::case("case02_co2_source"))),
+            Duration::ZERO,
+            Box::new(|update| panic!("unexpected update {:?}", update)),
+        );
+        let transient = case_input("case02_co2_source");
+        assert!(live.update(transient, RunOptions::default(), RunLimits::default()).is_err());
+    }
+}
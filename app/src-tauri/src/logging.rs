@@ -0,0 +1,69 @@
+//! Application log file: written in release builds too, so support cases from
+//! the field come with the engine invocations that led up to them.
+//!
+//! `tauri_plugin_log` writes `<log dir>/airsim.log` and renames it to
+//! `airsim_<date>.log` once it grows past `MAX_FILE_BYTES`.
+
+#[cfg(feature = "gui")]
+use std::path::Path;
+
+/// Base name of the log file in the app log directory.
+#[cfg(feature = "gui")]
+pub const LOG_FILE_NAME: &str = "airsim";
+
+/// Size at which the log file is rotated.
+#[cfg(feature = "gui")]
+pub const MAX_FILE_BYTES: u128 = 5 * 1024 * 1024;
+
+/// Rotated log files kept next to the current one.
+#[cfg(feature = "gui")]
+pub const KEEP_FILES: usize = 5;
+
+/// Engine stdout/stderr beyond this many bytes is logged as its tail only.
+const MAX_LOGGED_OUTPUT: usize = 64 * 1024;
+
+/// The last `MAX_LOGGED_OUTPUT` bytes of engine output, for logging. Error
+/// messages come at the end, so the tail is the part worth keeping.
+pub fn output_tail(text: &str) -> &str {
+    tail(text, MAX_LOGGED_OUTPUT)
+}
+
+/// The last `max_bytes` of the log, oldest rotated file first.
+#[cfg(feature = "gui")]
+pub fn read_logs(dir: &Path, max_bytes: u64) -> Result<String, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(String::new()),
+        Err(e) => return Err(format!("Failed to read log directory: {}", e)),
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(LOG_FILE_NAME) && name.ends_with(".log")
+        })
+        .filter_map(|e| Some((e.metadata().and_then(|m| m.modified()).ok()?, e.path())))
+        .collect();
+    files.sort();
+
+    let mut log = String::new();
+    for (_, path) in files {
+        match std::fs::read(&path) {
+            Ok(bytes) => log.push_str(&String::from_utf8_lossy(&bytes)),
+            Err(e) => log::warn!("Failed to read log file {}: {}", path.display(), e),
+        }
+    }
+    let keep = tail(&log, usize::try_from(max_bytes).unwrap_or(usize::MAX)).len();
+    log.drain(..log.len() - keep);
+    Ok(log)
+}
+
+/// At most the last `max_bytes` of `text`, starting on a character boundary.
+fn tail(text: &str, max_bytes: usize) -> &str {
+    let Some(mut start) = text.len().checked_sub(max_bytes) else { return text };
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
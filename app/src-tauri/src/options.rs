@@ -0,0 +1,121 @@
+//! Engine CLI options exposed through `run_engine`.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::discovery::EngineInfo;
+
+/// Airflow solver, passed as `-m <method>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SolverMethod {
+    /// Newton with trust-region step control (engine default)
+    #[default]
+    #[serde(rename = "tr")]
+    TrustRegion,
+    /// Sub-relaxation; slower but more robust on stiff networks
+    #[serde(rename = "sur")]
+    SubRelaxation,
+}
+
+impl SolverMethod {
+    pub fn flag(self) -> &'static str {
+        match self {
+            SolverMethod::TrustRegion => "tr",
+            SolverMethod::SubRelaxation => "sur",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Verbosity {
+    /// No `-v`: the engine prints nothing, so no progress events are emitted
+    Quiet,
+    /// `-v`: counts, solver summary and transient progress
+    #[default]
+    Verbose,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RunOptions {
+    pub method: SolverMethod,
+    /// Also write results to this HDF5 file (`--hdf5`); needs an HDF5-enabled engine
+    pub hdf5_output: Option<PathBuf>,
+    pub verbosity: Verbosity,
+}
+
+impl RunOptions {
+    /// Check the options against what the engine was built with.
+    pub fn validate(&self, info: &EngineInfo) -> Result<(), String> {
+        let Some(path) = &self.hdf5_output else { return Ok(()) };
+        if !path.is_absolute() {
+            return Err(format!("HDF5 output path must be absolute: {}", path.display()));
+        }
+        if !path.parent().is_some_and(|dir| dir.is_dir()) {
+            return Err(format!("HDF5 output directory does not exist: {}", path.display()));
+        }
+        if !info.hdf5 {
+            return Err(format!(
+                "Engine '{}' was built without HDF5 support; rebuild it with CONTAM_ENABLE_HDF5=ON",
+                info.location.path
+            ));
+        }
+        Ok(())
+    }
+
+    /// Append the option flags to an engine command.
+    pub fn apply(&self, cmd: &mut Command) {
+        cmd.arg("-m").arg(self.method.flag());
+        if let Some(path) = &self.hdf5_output {
+            cmd.arg("--hdf5").arg(path);
+        }
+        if self.verbosity == Verbosity::Verbose {
+            cmd.arg("-v");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{EnginePath, EngineSource};
+    use crate::testing::TestDir;
+
+    fn engine(hdf5: bool) -> EngineInfo {
+        EngineInfo {
+            location: EnginePath { path: "/opt/contam_engine".to_string(), source: EngineSource::Settings },
+            found: true,
+            version: Some("0.2.0".to_string()),
+            hdf5,
+            compatible: true,
+            min_version: "0.2.0".to_string(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn hdf5_output_needs_an_hdf5_engine() {
+        let dir = TestDir::new();
+        let options = RunOptions { hdf5_output: Some(dir.0.join("out.h5")), ..RunOptions::default() };
+
+        let error = options.validate(&engine(false)).unwrap_err();
+        assert!(error.contains("without HDF5 support"), "{}", error);
+        assert!(error.contains("/opt/contam_engine"), "{}", error);
+        options.validate(&engine(true)).unwrap();
+        RunOptions::default().validate(&engine(false)).unwrap();
+    }
+
+    #[test]
+    fn hdf5_output_needs_an_absolute_path_in_an_existing_directory() {
+        let dir = TestDir::new();
+        let with_output = |path: PathBuf| RunOptions { hdf5_output: Some(path), ..RunOptions::default() };
+
+        let error = with_output("out.h5".into()).validate(&engine(true)).unwrap_err();
+        assert!(error.contains("must be absolute"), "{}", error);
+        let error = with_output(dir.0.join("missing").join("out.h5")).validate(&engine(true)).unwrap_err();
+        assert!(error.contains("does not exist"), "{}", error);
+    }
+}
@@ -0,0 +1,130 @@
+//! Progress reporting parsed from the engine's verbose (`-v`) stdout.
+//!
+//! `TransientSimulation`'s progress callback prints `\r  t=<t>/<end>s` on every
+//! step; the simulation window itself is announced earlier as
+//! `Running transient simulation: <start>s to <end>s (dt=<dt>s)...`.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Minimum wall-clock gap between two emitted progress updates.
+const EMIT_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Progress {
+    /// Current simulation time [s]
+    pub time: f64,
+    /// Simulation end time [s]
+    pub end_time: f64,
+    /// Completion in 0..=100
+    pub percent: f64,
+    /// Estimated wall-clock time remaining [s], once enough progress has been made
+    pub eta_seconds: Option<f64>,
+}
+
+/// Turns engine stdout lines into throttled `Progress` updates.
+pub struct ProgressTracker {
+    started: Instant,
+    start_time: f64,
+    last_emit: Option<Instant>,
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        Self { started: Instant::now(), start_time: 0.0, last_emit: None }
+    }
+
+    /// Feed one `\r`- or `\n`-delimited stdout segment. Returns an update when
+    /// the segment is a progress line and the throttle interval has elapsed.
+    pub fn feed(&mut self, line: &str) -> Option<Progress> {
+        let line = line.trim();
+        if let Some(start) = parse_window_start(line) {
+            self.start_time = start;
+            return None;
+        }
+        let (time, end_time) = parse_step(line)?;
+
+        let now = Instant::now();
+        let done = time >= end_time;
+        if !done && self.last_emit.is_some_and(|t| now.duration_since(t) < EMIT_INTERVAL) {
+            return None;
+        }
+        self.last_emit = Some(now);
+
+        let span = end_time - self.start_time;
+        let fraction = if span > 0.0 { ((time - self.start_time) / span).clamp(0.0, 1.0) } else { 1.0 };
+        let elapsed = now.duration_since(self.started).as_secs_f64();
+        let eta_seconds = (fraction > 0.0).then(|| elapsed * (1.0 - fraction) / fraction);
+
+        Some(Progress { time, end_time, percent: fraction * 100.0, eta_seconds })
+    }
+}
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether a stdout segment is one of the per-step `t=<t>/<end>s` lines.
+pub fn is_progress_line(line: &str) -> bool {
+    parse_step(line.trim()).is_some()
+}
+
+/// `t=<t>/<end>s` → `(t, end)`
+fn parse_step(line: &str) -> Option<(f64, f64)> {
+    let rest = line.strip_prefix("t=")?.strip_suffix('s')?;
+    let (t, end) = rest.split_once('/')?;
+    Some((t.parse().ok()?, end.parse().ok()?))
+}
+
+/// `Running transient simulation: <start>s to <end>s ...` → `start`
+fn parse_window_start(line: &str) -> Option<f64> {
+    let rest = line.strip_prefix("Running transient simulation:")?;
+    let (start, _) = rest.trim().split_once("s to ")?;
+    start.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_step_lines_only() {
+        assert_eq!(parse_step("t=150/3600s"), Some((150.0, 3600.0)));
+        assert_eq!(parse_step("t=1.5e2/3.6e3s"), Some((150.0, 3600.0)));
+        assert!(is_progress_line("\r  t=150/3600s\n"));
+
+        for line in ["", "t=", "t=150s", "t=150/3600", "t=abc/3600s", "t=150/s", "x=150/3600s", "Done."] {
+            assert_eq!(parse_step(line), None, "{:?}", line);
+            assert!(!is_progress_line(line), "{:?}", line);
+        }
+        assert_eq!(parse_window_start("Running transient simulation: 600s to 3600s (dt=60s)..."), Some(600.0));
+        assert_eq!(parse_window_start("Running transient simulation: soon"), None);
+    }
+
+    #[test]
+    fn progress_is_measured_from_the_window_start() {
+        let mut tracker = ProgressTracker::new();
+        assert!(tracker.feed("Running transient simulation: 600s to 3600s (dt=60s)...").is_none());
+        assert!(tracker.feed("Solver: trust region").is_none());
+
+        let progress = tracker.feed("  t=1350/3600s").unwrap();
+        assert_eq!((progress.time, progress.end_time), (1350.0, 3600.0));
+        assert_eq!(progress.percent, 25.0);
+        assert!(progress.eta_seconds.is_some());
+    }
+
+    #[test]
+    fn updates_are_throttled_except_the_last() {
+        let mut tracker = ProgressTracker::new();
+        assert!(tracker.feed("t=0/100s").unwrap().eta_seconds.is_none(), "no estimate before any progress");
+        assert!(tracker.feed("t=10/100s").is_none(), "too soon after the previous update");
+        assert_eq!(tracker.feed("t=100/100s").unwrap().percent, 100.0, "the final step always gets through");
+
+        std::thread::sleep(EMIT_INTERVAL + Duration::from_millis(20));
+        assert_eq!(tracker.feed("t=20/100s").unwrap().time, 20.0);
+    }
+}
@@ -0,0 +1,83 @@
+//! Results of recent runs, kept in the backend so the webview fetches an
+//! overview and then slices instead of parsing the whole output.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::results::{EngineResult, NodeInfo, SpeciesInfo, SteadyResult, TransientResult};
+
+/// Results held in memory; older ones are reloaded from the history on demand.
+const MAX_RESULTS: usize = 4;
+
+#[derive(Default)]
+pub struct ResultStore {
+    /// Most recently used last
+    results: Mutex<VecDeque<(String, Arc<EngineResult>)>>,
+}
+
+impl ResultStore {
+    pub fn insert(&self, run_id: &str, result: Arc<EngineResult>) {
+        let mut results = self.results.lock().unwrap();
+        results.retain(|(id, _)| id != run_id);
+        results.push_back((run_id.to_string(), result));
+        while results.len() > MAX_RESULTS {
+            results.pop_front();
+        }
+    }
+
+    pub fn get(&self, run_id: &str) -> Option<Arc<EngineResult>> {
+        let mut results = self.results.lock().unwrap();
+        let index = results.iter().position(|(id, _)| id == run_id)?;
+        let entry = results.remove(index)?;
+        let result = entry.1.clone();
+        results.push_back(entry);
+        Some(result)
+    }
+}
+
+/// What the UI needs to lay out a result before fetching any series. Steady
+/// results are small and sent whole.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ResultOverview {
+    Steady(SteadyResult),
+    Transient(TransientOverview),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransientOverview {
+    pub completed: bool,
+    pub total_steps: usize,
+    pub species: Vec<SpeciesInfo>,
+    pub nodes: Vec<NodeInfo>,
+    pub link_count: usize,
+    /// Output time of the first and last step [s]
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+}
+
+impl From<&EngineResult> for ResultOverview {
+    fn from(result: &EngineResult) -> Self {
+        match result {
+            EngineResult::Steady(steady) => ResultOverview::Steady(steady.clone()),
+            EngineResult::Transient(transient) => ResultOverview::Transient(TransientOverview::from(transient)),
+        }
+    }
+}
+
+impl From<&TransientResult> for TransientOverview {
+    fn from(result: &TransientResult) -> Self {
+        Self {
+            completed: result.completed,
+            total_steps: result.total_steps,
+            species: result.species.clone(),
+            nodes: result.nodes.clone(),
+            link_count: result.link_count(),
+            start_time: result.times().first().copied(),
+            end_time: result.times().last().copied(),
+        }
+    }
+}
@@ -0,0 +1,954 @@
+//! Typed model of the engine output written by `JsonWriter`, with the lookups
+//! post-processing and export build on.
+//!
+//! Output is parsed as a stream: the `timeSeries` of transient output goes
+//! straight into `Column`s indexed by step, node and species, without holding
+//! the file text or a tree of per-step arrays. `nlohmann::json` writes
+//! non-finite numbers as `null`; those read back as NaN so a diverged value
+//! does not make the whole output unreadable.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::columns::{Column, ColumnBuilder};
+
+/// Part of a transient result for the UI to fetch instead of the whole
+/// output. Series are returned row by row for each selected step as
+/// `[time, (converged, iterations,) pressures.., massFlows.., concentrations..]`,
+/// with the columns in the order given here and concentrations node-major.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SliceQuery {
+    /// [s]; unbounded if omitted
+    pub start_time: Option<f64>,
+    /// [s]; unbounded if omitted
+    pub end_time: Option<f64>,
+    /// Nodes for the pressure and concentration columns
+    #[serde(default)]
+    pub node_ids: Vec<i32>,
+    #[serde(default)]
+    pub species_ids: Vec<i32>,
+    /// Positions in the input's `links`
+    #[serde(default)]
+    pub link_indices: Vec<usize>,
+    /// Thin the window to about this many evenly spaced steps, for charts
+    pub max_points: Option<usize>,
+    /// Include each step's airflow solve: converged as 1 or 0, and iterations
+    #[serde(default)]
+    pub solver: bool,
+}
+
+/// Output of either kind of run, as found in `output.json`.
+#[allow(clippy::large_enum_variant)] // Shared behind an `Arc`, never moved around
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum EngineResult {
+    Transient(TransientResult),
+    Steady(SteadyResult),
+}
+
+impl EngineResult {
+    /// Parse engine output. Series too large for memory are moved to files in
+    /// `spill_dir`; without one everything stays in memory.
+    pub fn from_reader<R: Read>(reader: R, spill_dir: Option<&Path>) -> Result<Self, String> {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let result = deserializer
+            .deserialize_map(OutputVisitor { spill_dir })
+            .and_then(|result| deserializer.end().map(|()| result))
+            .map_err(|e| e.to_string())?;
+        Ok(result)
+    }
+
+    /// `solver.maxResidual` of steady output [kg/s]
+    pub fn max_residual(&self) -> Option<f64> {
+        match self {
+            EngineResult::Steady(steady) => steady.solver.max_residual,
+            EngineResult::Transient(_) => None,
+        }
+    }
+
+    /// Whether the airflow solve converged: the steady solve, or every output step
+    pub fn converged(&self) -> bool {
+        match self {
+            EngineResult::Steady(steady) => steady.solver.converged,
+            EngineResult::Transient(transient) => transient.converged.iter().all(|&c| c),
+        }
+    }
+
+    /// Node pressures of the steady solution or the last transient step, by
+    /// node ID [Pa]. Nodes the solver left at NaN are omitted.
+    pub fn final_pressures(&self) -> BTreeMap<i32, f64> {
+        let pressures: Vec<(i32, f64)> = match self {
+            EngineResult::Steady(steady) => steady.nodes.iter().map(|n| (n.id, n.pressure)).collect(),
+            EngineResult::Transient(transient) => {
+                let Some(last) = transient.step_count().checked_sub(1) else { return BTreeMap::new() };
+                transient.nodes.iter().enumerate().map(|(i, n)| (n.id, transient.pressure(last, i))).collect()
+            }
+        };
+        pressures.into_iter().filter(|(_, p)| p.is_finite()).collect()
+    }
+
+    /// Steady solver iterations, or the most any transient output step needed
+    pub fn iterations(&self) -> u32 {
+        match self {
+            EngineResult::Steady(steady) => steady.solver.iterations,
+            EngineResult::Transient(transient) => transient.iterations.iter().copied().max().unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SteadyResult {
+    pub solver: SolverInfo,
+    pub nodes: Vec<NodeResult>,
+    pub links: Vec<LinkResult>,
+}
+
+impl SteadyResult {
+    pub fn node(&self, id: i32) -> Option<&NodeResult> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    pub fn link(&self, id: i32) -> Option<&LinkResult> {
+        self.links.iter().find(|l| l.id == id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolverInfo {
+    pub converged: bool,
+    pub iterations: u32,
+    /// [kg/s]
+    pub max_residual: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeResult {
+    pub id: i32,
+    pub name: String,
+    /// Gauge pressure [Pa]
+    #[serde(deserialize_with = "number")]
+    pub pressure: f64,
+    /// [kg/m³]
+    #[serde(deserialize_with = "number")]
+    pub density: f64,
+    /// [K]
+    pub temperature: f64,
+    /// [m]
+    pub elevation: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkResult {
+    pub id: i32,
+    /// Node ID
+    pub from: i32,
+    /// Node ID
+    pub to: i32,
+    /// Positive from `from` to `to` [kg/s]
+    #[serde(deserialize_with = "number")]
+    pub mass_flow: f64,
+    /// [m³/s]
+    #[serde(rename = "volumeFlow_m3s", deserialize_with = "number")]
+    pub volume_flow: f64,
+}
+
+/// Transient output. Serializes back to the `JsonWriter` layout.
+#[derive(Debug)]
+pub struct TransientResult {
+    /// `false` if the run stopped before `endTime`
+    pub completed: bool,
+    pub total_steps: usize,
+    pub species: Vec<SpeciesInfo>,
+    pub nodes: Vec<NodeInfo>,
+    shape: StepShape,
+    /// [s]
+    times: Vec<f64>,
+    converged: Vec<bool>,
+    iterations: Vec<u32>,
+    /// `[step][node]` [Pa]
+    pressures: Column,
+    /// `[step][link]` [kg/s]
+    mass_flows: Column,
+    /// `[step][node][species]` [kg/m³]
+    concentrations: Column,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeciesInfo {
+    pub id: i32,
+    pub name: String,
+    /// [kg/mol]
+    pub molar_mass: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeInfo {
+    pub id: i32,
+    pub name: String,
+    /// `ambient` for known-pressure nodes, `normal` otherwise
+    #[serde(rename = "type")]
+    pub node_type: String,
+}
+
+/// State at one output time. Arrays are indexed like `TransientResult::nodes`
+/// and, for links, like the input's `links`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeStep {
+    /// [s]
+    pub time: f64,
+    pub airflow: StepAirflow,
+    /// `[node][species]` [kg/m³]; empty without species
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub concentrations: Vec<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepAirflow {
+    pub converged: bool,
+    pub iterations: u32,
+    /// [Pa]
+    pub pressures: Vec<f64>,
+    /// [kg/s]
+    pub mass_flows: Vec<f64>,
+}
+
+/// Values per step; the same for every step of an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StepShape {
+    nodes: usize,
+    links: usize,
+    /// 0 without species
+    species: usize,
+}
+
+impl TransientResult {
+    pub fn node_index(&self, node_id: i32) -> Option<usize> {
+        self.nodes.iter().position(|n| n.id == node_id)
+    }
+
+    pub fn species_index(&self, species_id: i32) -> Option<usize> {
+        self.species.iter().position(|s| s.id == species_id)
+    }
+
+    /// Output steps actually present; less than `total_steps` only if the
+    /// output is inconsistent.
+    pub fn step_count(&self) -> usize {
+        self.times.len()
+    }
+
+    /// Columns of `StepAirflow::mass_flows`
+    pub fn link_count(&self) -> usize {
+        self.shape.links
+    }
+
+    /// Output times [s]
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    /// Pressure by step and node index [Pa]
+    pub fn pressure(&self, step: usize, node: usize) -> f64 {
+        value(&self.pressures, step, self.shape.nodes, node)
+    }
+
+    /// Mass flow by step and link index [kg/s]
+    pub fn mass_flow(&self, step: usize, link: usize) -> f64 {
+        value(&self.mass_flows, step, self.shape.links, link)
+    }
+
+    /// Concentration by step, node index and species index [kg/m³]
+    pub fn concentration(&self, step: usize, node: usize, species: usize) -> f64 {
+        if species >= self.shape.species {
+            return f64::NAN;
+        }
+        value(&self.concentrations, step, self.shape.nodes * self.shape.species, node * self.shape.species + species)
+    }
+
+    /// Concentration of a species in a node at every output time [kg/m³].
+    pub fn concentration_series(&self, node_id: i32, species_id: i32) -> Option<Vec<f64>> {
+        let node = self.node_index(node_id)?;
+        let species = self.species_index(species_id)?;
+        (species < self.shape.species).then(|| self.series(|step| self.concentration(step, node, species)))
+    }
+
+    /// Pressure of a node at every output time [Pa].
+    pub fn pressure_series(&self, node_id: i32) -> Option<Vec<f64>> {
+        let node = self.node_index(node_id)?;
+        Some(self.series(|step| self.pressure(step, node)))
+    }
+
+    /// Mass flow through a link at every output time [kg/s]. Transient
+    /// output does not list links, so they are addressed by their position
+    /// in the input's `links`, not by link ID.
+    pub fn link_flow_series(&self, link_index: usize) -> Option<Vec<f64>> {
+        (link_index < self.shape.links).then(|| self.series(|step| self.mass_flow(step, link_index)))
+    }
+
+    pub fn step(&self, step: usize) -> Option<TimeStep> {
+        let time = *self.times.get(step)?;
+        let concentrations = (0..self.nodes_with_concentrations())
+            .map(|node| (0..self.shape.species).map(|s| self.concentration(step, node, s)).collect())
+            .collect();
+        Some(TimeStep {
+            time,
+            airflow: StepAirflow {
+                converged: self.converged[step],
+                iterations: self.iterations[step],
+                pressures: (0..self.shape.nodes).map(|node| self.pressure(step, node)).collect(),
+                mass_flows: (0..self.shape.links).map(|link| self.mass_flow(step, link)).collect(),
+            },
+            concentrations,
+        })
+    }
+
+    /// The state at time `t`: the last output step at or before it.
+    pub fn snapshot(&self, t: f64) -> Option<TimeStep> {
+        let after = self.times.partition_point(|&time| time <= t);
+        self.step(after.checked_sub(1)?)
+    }
+
+    /// The values selected by `query`, laid out as described on `SliceQuery`.
+    pub fn slice(&self, query: &SliceQuery) -> Result<Vec<f64>, String> {
+        let nodes = query
+            .node_ids
+            .iter()
+            .map(|&id| self.node_index(id).ok_or_else(|| format!("No node with ID {}", id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let species = query
+            .species_ids
+            .iter()
+            .map(|&id| self.species_index(id).ok_or_else(|| format!("No species with ID {}", id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(link) = query.link_indices.iter().find(|&&l| l >= self.shape.links) {
+            return Err(format!("No link at index {}", link));
+        }
+
+        let start = query.start_time.map_or(0, |t| self.times.partition_point(|&time| time < t));
+        let end = query.end_time.map_or(self.times.len(), |t| self.times.partition_point(|&time| time <= t));
+        let window = start..end.max(start);
+        let stride = match query.max_points {
+            Some(max) if max > 0 && window.len() > max => window.len().div_ceil(max),
+            _ => 1,
+        };
+        let mut steps: Vec<usize> = window.clone().step_by(stride).collect();
+        // Keep the end of the window when thinning skips it
+        if let Some(last) = window.clone().last().filter(|&last| steps.last() != Some(&last)) {
+            steps.push(last);
+        }
+
+        let solver = if query.solver { 2 } else { 0 };
+        let row = 1 + solver + nodes.len() + query.link_indices.len() + nodes.len() * species.len();
+        let mut values = Vec::with_capacity(row * steps.len());
+        for step in steps {
+            values.push(self.times[step]);
+            if query.solver {
+                values.push(if self.converged[step] { 1.0 } else { 0.0 });
+                values.push(f64::from(self.iterations[step]));
+            }
+            values.extend(nodes.iter().map(|&n| self.pressure(step, n)));
+            values.extend(query.link_indices.iter().map(|&l| self.mass_flow(step, l)));
+            for &n in &nodes {
+                values.extend(species.iter().map(|&s| self.concentration(step, n, s)));
+            }
+        }
+        Ok(values)
+    }
+
+    /// Concentrations of the last output step by node ID and species ID
+    /// [kg/m³]. Values the solver left at NaN are omitted.
+    pub fn final_concentrations(&self) -> BTreeMap<i32, BTreeMap<i32, f64>> {
+        let Some(last) = self.step_count().checked_sub(1) else { return BTreeMap::new() };
+        (0..self.nodes_with_concentrations())
+            .map(|node| {
+                let by_species = (self.species.iter().enumerate())
+                    .map(|(s, species)| (species.id, self.concentration(last, node, s)))
+                    .filter(|(_, c)| c.is_finite())
+                    .collect();
+                (self.nodes[node].id, by_species)
+            })
+            .collect()
+    }
+
+    /// `JsonWriter` omits concentrations entirely without species.
+    fn nodes_with_concentrations(&self) -> usize {
+        if self.shape.species > 0 {
+            self.shape.nodes
+        } else {
+            0
+        }
+    }
+
+    fn series(&self, value: impl Fn(usize) -> f64) -> Vec<f64> {
+        (0..self.times.len()).map(value).collect()
+    }
+}
+
+/// Joins the results of consecutive segments of one transient run into a
+/// single result, moving large series to files as parsing does.
+pub struct TransientStitcher {
+    /// Taken from the first segment; later ones must match it
+    header: Option<(Vec<SpeciesInfo>, Vec<NodeInfo>, StepShape)>,
+    times: Vec<f64>,
+    converged: Vec<bool>,
+    iterations: Vec<u32>,
+    pressures: ColumnBuilder,
+    mass_flows: ColumnBuilder,
+    concentrations: ColumnBuilder,
+}
+
+impl TransientStitcher {
+    pub fn new(spill_dir: Option<&Path>) -> Self {
+        Self {
+            header: None,
+            times: Vec::new(),
+            converged: Vec::new(),
+            iterations: Vec::new(),
+            pressures: ColumnBuilder::new(spill_dir),
+            mass_flows: ColumnBuilder::new(spill_dir),
+            concentrations: ColumnBuilder::new(spill_dir),
+        }
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.times.len()
+    }
+
+    /// Append the steps of the next segment. A segment's first step is the
+    /// state the previous one ended in; that repeated step is skipped.
+    pub fn push(&mut self, segment: &TransientResult) -> Result<(), String> {
+        match &self.header {
+            None => self.header = Some((segment.species.clone(), segment.nodes.clone(), segment.shape)),
+            Some((_, nodes, shape)) => {
+                let same_nodes = nodes.iter().map(|n| n.id).eq(segment.nodes.iter().map(|n| n.id));
+                if *shape != segment.shape || !same_nodes {
+                    return Err("Segment output differs in layout from the first segment".to_string());
+                }
+            }
+        }
+        let repeated = match (self.times.last(), segment.times.first()) {
+            (Some(&last), Some(&first)) => (first - last).abs() <= 1e-6 * last.abs().max(1.0),
+            _ => false,
+        };
+        let shape = segment.shape;
+        for step in usize::from(repeated)..segment.step_count() {
+            self.times.push(segment.times[step]);
+            self.converged.push(segment.converged[step]);
+            self.iterations.push(segment.iterations[step]);
+            copy_row(&segment.pressures, step, shape.nodes, &mut self.pressures)?;
+            copy_row(&segment.mass_flows, step, shape.links, &mut self.mass_flows)?;
+            copy_row(&segment.concentrations, step, shape.nodes * shape.species, &mut self.concentrations)?;
+        }
+        Ok(())
+    }
+
+    /// The joined result; `completed` if the last segment reached the end time.
+    pub fn finish(self, completed: bool) -> Result<TransientResult, String> {
+        let Some((species, nodes, shape)) = self.header else { return Err("No segment to stitch".to_string()) };
+        let stored = |column: ColumnBuilder| column.finish().map_err(|e| format!("Failed to store series: {}", e));
+        Ok(TransientResult {
+            completed,
+            total_steps: self.times.len(),
+            species,
+            nodes,
+            shape,
+            times: self.times,
+            converged: self.converged,
+            iterations: self.iterations,
+            pressures: stored(self.pressures)?,
+            mass_flows: stored(self.mass_flows)?,
+            concentrations: stored(self.concentrations)?,
+        })
+    }
+}
+
+fn copy_row(column: &Column, step: usize, width: usize, into: &mut ColumnBuilder) -> Result<(), String> {
+    for index in 0..width {
+        into.push(value(column, step, width, index)).map_err(|e| format!("Failed to store series: {}", e))?;
+    }
+    Ok(())
+}
+
+/// `column[step][index]` for rows of `width`; NaN past the end.
+fn value(column: &Column, step: usize, width: usize, index: usize) -> f64 {
+    if index >= width {
+        return f64::NAN;
+    }
+    column.get(step * width + index).unwrap_or(f64::NAN)
+}
+
+impl Serialize for TransientResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Steps<'a>(&'a TransientResult);
+
+        impl Serialize for Steps<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut seq = serializer.serialize_seq(Some(self.0.step_count()))?;
+                for step in (0..self.0.step_count()).filter_map(|step| self.0.step(step)) {
+                    seq.serialize_element(&step)?;
+                }
+                seq.end()
+            }
+        }
+
+        let mut result = serializer.serialize_struct("TransientResult", 5)?;
+        result.serialize_field("completed", &self.completed)?;
+        result.serialize_field("totalSteps", &self.total_steps)?;
+        result.serialize_field("species", &self.species)?;
+        result.serialize_field("nodes", &self.nodes)?;
+        result.serialize_field("timeSeries", &Steps(self))?;
+        result.end()
+    }
+}
+
+/// Top-level keys of either kind of output, in any order.
+struct OutputVisitor<'a> {
+    spill_dir: Option<&'a Path>,
+}
+
+impl<'de> Visitor<'de> for OutputVisitor<'_> {
+    type Value = EngineResult;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("engine output")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<EngineResult, A::Error> {
+        let mut solver = None;
+        let mut links = None;
+        // Node lists differ between steady and transient output; they are
+        // short, so keep them untyped until the kind is known
+        let mut nodes: Option<Value> = None;
+        let mut completed = None;
+        let mut total_steps = None;
+        let mut species = None;
+        let mut steps: Option<StepColumns> = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "solver" => solver = Some(map.next_value()?),
+                "links" => links = Some(map.next_value()?),
+                "nodes" => nodes = Some(map.next_value()?),
+                "completed" => completed = Some(map.next_value()?),
+                "totalSteps" => total_steps = Some(map.next_value()?),
+                "species" => species = Some(map.next_value()?),
+                "timeSeries" => steps = Some(map.next_value_seed(StepsSeed { spill_dir: self.spill_dir })?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        let nodes = nodes.ok_or_else(|| de::Error::missing_field("nodes"))?;
+
+        let Some(steps) = steps else {
+            return Ok(EngineResult::Steady(SteadyResult {
+                solver: solver.ok_or_else(|| de::Error::missing_field("solver"))?,
+                nodes: serde_json::from_value(nodes).map_err(de::Error::custom)?,
+                links: links.ok_or_else(|| de::Error::missing_field("links"))?,
+            }));
+        };
+        let nodes: Vec<NodeInfo> = serde_json::from_value(nodes).map_err(de::Error::custom)?;
+        let species: Vec<SpeciesInfo> = species.unwrap_or_default();
+        let shape = steps.shape.unwrap_or(StepShape { nodes: nodes.len(), links: 0, species: 0 });
+        if shape.nodes != nodes.len() {
+            return Err(de::Error::custom(format!("{} nodes but {} pressures per step", nodes.len(), shape.nodes)));
+        }
+        if shape.species != 0 && shape.species != species.len() {
+            let message = format!("{} species but {} concentrations per node", species.len(), shape.species);
+            return Err(de::Error::custom(message));
+        }
+        Ok(EngineResult::Transient(TransientResult {
+            completed: completed.ok_or_else(|| de::Error::missing_field("completed"))?,
+            total_steps: total_steps.unwrap_or(steps.times.len()),
+            species,
+            nodes,
+            shape,
+            times: steps.times,
+            converged: steps.converged,
+            iterations: steps.iterations,
+            pressures: steps.pressures.finish().map_err(de::Error::custom)?,
+            mass_flows: steps.mass_flows.finish().map_err(de::Error::custom)?,
+            concentrations: steps.concentrations.finish().map_err(de::Error::custom)?,
+        }))
+    }
+}
+
+/// `timeSeries` as it is being read.
+struct StepColumns {
+    shape: Option<StepShape>,
+    times: Vec<f64>,
+    converged: Vec<bool>,
+    iterations: Vec<u32>,
+    pressures: ColumnBuilder,
+    mass_flows: ColumnBuilder,
+    concentrations: ColumnBuilder,
+}
+
+struct StepsSeed<'a> {
+    spill_dir: Option<&'a Path>,
+}
+
+impl<'de> DeserializeSeed<'de> for StepsSeed<'_> {
+    type Value = StepColumns;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<StepColumns, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StepsSeed<'_> {
+    type Value = StepColumns;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of time steps")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<StepColumns, A::Error> {
+        let mut columns = StepColumns {
+            shape: None,
+            times: Vec::new(),
+            converged: Vec::new(),
+            iterations: Vec::new(),
+            pressures: ColumnBuilder::new(self.spill_dir),
+            mass_flows: ColumnBuilder::new(self.spill_dir),
+            concentrations: ColumnBuilder::new(self.spill_dir),
+        };
+        while seq.next_element_seed(StepSeed { columns: &mut columns })?.is_some() {}
+        Ok(columns)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "camelCase")]
+enum StepField {
+    Time,
+    Airflow,
+    Concentrations,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "camelCase")]
+enum AirflowField {
+    Converged,
+    Iterations,
+    Pressures,
+    MassFlows,
+    #[serde(other)]
+    Other,
+}
+
+/// One `timeSeries` entry, appended to the columns.
+struct StepSeed<'a> {
+    columns: &'a mut StepColumns,
+}
+
+impl<'de> DeserializeSeed<'de> for StepSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StepSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a time step")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let columns = self.columns;
+        let step = columns.times.len();
+        let mut time = None;
+        let mut converged = false;
+        let mut iterations = 0;
+        let mut shape = StepShape { nodes: 0, links: 0, species: 0 };
+        let mut rows = 0;
+        while let Some(field) = map.next_key()? {
+            match field {
+                StepField::Time => time = Some(map.next_value::<Option<f64>>()?.unwrap_or(f64::NAN)),
+                StepField::Airflow => {
+                    let airflow = map.next_value_seed(AirflowSeed { columns: &mut *columns })?;
+                    (converged, iterations, shape.nodes, shape.links) = airflow;
+                }
+                StepField::Concentrations => {
+                    (rows, shape.species) = map.next_value_seed(TableSeed { column: &mut columns.concentrations })?;
+                }
+                StepField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if rows != 0 && rows != shape.nodes {
+            let message = format!("timeSeries[{}] has concentrations for {} of {} nodes", step, rows, shape.nodes);
+            return Err(de::Error::custom(message));
+        }
+        match columns.shape {
+            None => columns.shape = Some(shape),
+            Some(expected) if expected != shape => {
+                return Err(de::Error::custom(format!("timeSeries[{}] differs in size from the first step", step)));
+            }
+            Some(_) => {}
+        }
+        columns.times.push(time.ok_or_else(|| de::Error::missing_field("time"))?);
+        columns.converged.push(converged);
+        columns.iterations.push(iterations);
+        Ok(())
+    }
+}
+
+/// A step's `airflow`: `(converged, iterations, pressure count, flow count)`.
+struct AirflowSeed<'a> {
+    columns: &'a mut StepColumns,
+}
+
+impl<'de> DeserializeSeed<'de> for AirflowSeed<'_> {
+    type Value = (bool, u32, usize, usize);
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for AirflowSeed<'_> {
+    type Value = (bool, u32, usize, usize);
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("airflow results")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let columns = self.columns;
+        let mut airflow = (false, 0, 0, 0);
+        while let Some(field) = map.next_key()? {
+            match field {
+                AirflowField::Converged => airflow.0 = map.next_value()?,
+                AirflowField::Iterations => airflow.1 = map.next_value()?,
+                AirflowField::Pressures => airflow.2 = map.next_value_seed(ValuesSeed { column: &mut columns.pressures })?,
+                AirflowField::MassFlows => {
+                    airflow.3 = map.next_value_seed(ValuesSeed { column: &mut columns.mass_flows })?
+                }
+                AirflowField::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(airflow)
+    }
+}
+
+/// An array of numbers appended to a column; yields its length.
+struct ValuesSeed<'a> {
+    column: &'a mut ColumnBuilder,
+}
+
+impl<'de> DeserializeSeed<'de> for ValuesSeed<'_> {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ValuesSeed<'_> {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of numbers")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<usize, A::Error> {
+        let mut count = 0;
+        while let Some(value) = seq.next_element::<Option<f64>>()? {
+            self.column.push(value.unwrap_or(f64::NAN)).map_err(de::Error::custom)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// An array of equally long number arrays appended to a column; yields
+/// `(rows, columns)`.
+struct TableSeed<'a> {
+    column: &'a mut ColumnBuilder,
+}
+
+impl<'de> DeserializeSeed<'de> for TableSeed<'_> {
+    type Value = (usize, usize);
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(usize, usize), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for TableSeed<'_> {
+    type Value = (usize, usize);
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of lists of numbers")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(usize, usize), A::Error> {
+        let column = self.column;
+        let mut rows = 0;
+        let mut width = None;
+        while let Some(len) = seq.next_element_seed(ValuesSeed { column: &mut *column })? {
+            let expected = *width.get_or_insert(len);
+            if len != expected {
+                return Err(de::Error::custom(format!("row {} has {} values instead of {}", rows, len, expected)));
+            }
+            rows += 1;
+        }
+        Ok((rows, width.unwrap_or(0)))
+    }
+}
+
+fn number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::runner::MockRunner;
+
+    fn transient() -> TransientResult {
+        let file = File::open(MockRunner::case_dir("case02_co2_source").join("output.json")).unwrap();
+        match EngineResult::from_reader(file, None).unwrap() {
+            EngineResult::Transient(transient) => transient,
+            EngineResult::Steady(_) => panic!("case02 is transient"),
+        }
+    }
+
+    /// case02's output and input as plain JSON, to check the accessors against
+    fn raw(file: &str) -> Value {
+        let file = File::open(MockRunner::case_dir("case02_co2_source").join(file)).unwrap();
+        serde_json::from_reader(file).unwrap()
+    }
+
+    /// `timeSeries[*]` followed by `path`
+    fn raw_series(output: &Value, path: &[&str], index: &[usize]) -> Vec<f64> {
+        let steps = output["timeSeries"].as_array().unwrap();
+        steps
+            .iter()
+            .map(|step| {
+                let field = path.iter().fold(step, |v, key| &v[*key]);
+                index.iter().fold(field, |v, &i| &v[i]).as_f64().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn series_follow_the_output_by_id() {
+        let result = transient();
+        let output = raw("output.json");
+
+        // Node 1 (Office) is the second node, CO2 (species 0) the first species
+        let office = result.node_index(1).unwrap();
+        assert_eq!(office, 1);
+        assert_eq!(result.pressure_series(1).unwrap(), raw_series(&output, &["airflow", "pressures"], &[office]));
+        let co2 = result.concentration_series(1, 0).unwrap();
+        assert_eq!(co2, raw_series(&output, &["concentrations"], &[office, 0]));
+        assert!(co2.last().unwrap() > co2.first().unwrap(), "the source raises the office's CO2");
+
+        assert!(result.pressure_series(99).is_none());
+        assert!(result.concentration_series(99, 0).is_none());
+        assert!(result.concentration_series(1, 99).is_none());
+    }
+
+    #[test]
+    fn link_series_are_addressed_by_input_position() {
+        let result = transient();
+        let output = raw("output.json");
+        let input = raw("input.json");
+        let links = input["links"].as_array().unwrap();
+        assert_eq!(result.link_count(), links.len());
+
+        // case02's links have IDs 1 and 2, so index 1 is link 2 and there is no index 2
+        assert_eq!(links[1]["id"], 2);
+        for (index, _) in links.iter().enumerate() {
+            let flows = result.link_flow_series(index).unwrap();
+            assert_eq!(flows, raw_series(&output, &["airflow", "massFlows"], &[index]));
+        }
+        assert!(result.link_flow_series(links.len()).is_none());
+    }
+
+    #[test]
+    fn snapshots_hold_the_last_step_at_or_before_a_time() {
+        let result = transient();
+        let times = result.times();
+        let (t3, t4) = (times[3], times[4]);
+
+        assert_eq!(result.snapshot(t3).unwrap().time, t3);
+        let between = result.snapshot((t3 + t4) / 2.0).unwrap();
+        assert_eq!(between.time, t3);
+        assert_eq!(between.airflow.pressures, result.step(3).unwrap().airflow.pressures);
+        let co2 = raw_series(&raw("output.json"), &["concentrations"], &[1, 0]);
+        assert_eq!(between.concentrations[1][0], co2[3]);
+
+        assert!(result.snapshot(times[0] - 1.0).is_none(), "nothing before the first output");
+        assert_eq!(result.snapshot(f64::INFINITY).unwrap().time, *times.last().unwrap());
+    }
+
+    #[test]
+    fn slices_carry_per_step_solver_stats() {
+        let result = transient();
+        let node = result.nodes[0].id;
+        let query = SliceQuery { node_ids: vec![node], solver: true, ..SliceQuery::default() };
+        let values = result.slice(&query).unwrap();
+        let rows: Vec<&[f64]> = values.chunks(4).collect();
+        assert_eq!(rows.len(), result.step_count());
+        for (k, row) in rows.iter().enumerate() {
+            let step = result.step(k).unwrap();
+            assert_eq!(row[0], step.time);
+            assert_eq!(row[1], if step.airflow.converged { 1.0 } else { 0.0 });
+            assert_eq!(row[2], f64::from(step.airflow.iterations));
+            assert_eq!(row[3].to_bits(), step.airflow.pressures[0].to_bits());
+        }
+
+        // Thinned slices keep the last step
+        let thinned = result.slice(&SliceQuery { max_points: Some(10), ..query }).unwrap();
+        assert!(thinned.len() / 4 <= 11);
+        assert_eq!(thinned[thinned.len() - 4], *result.times().last().unwrap());
+    }
+
+    #[test]
+    fn slices_the_views_ask_for() {
+        let result = transient();
+
+        // The output times, fetched once per result
+        assert_eq!(result.slice(&SliceQuery::default()).unwrap(), result.times());
+
+        // One step for the canvas
+        let time = result.times()[3];
+        let query = SliceQuery {
+            start_time: Some(time),
+            end_time: Some(time),
+            node_ids: result.nodes.iter().map(|n| n.id).collect(),
+            link_indices: (0..result.link_count()).collect(),
+            ..SliceQuery::default()
+        };
+        let values = result.slice(&query).unwrap();
+        let step = result.step(3).unwrap();
+        assert_eq!(values.len(), 1 + step.airflow.pressures.len() + step.airflow.mass_flows.len());
+        assert_eq!(values[0], time);
+        assert_eq!(values[1..1 + result.nodes.len()], step.airflow.pressures[..]);
+    }
+}
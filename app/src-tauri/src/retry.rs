@@ -0,0 +1,219 @@
+//! Opt-in retry of runs whose airflow solve does not converge.
+//!
+//! A run that exits with code 2, or whose transient output has steps that did
+//! not converge, is run once more with the other solver method, and for
+//! transient models optionally with a more patient airflow solve. The better
+//! of the two results is returned, with both attempts listed on it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::engine::RunOutcome;
+use crate::error::EngineError;
+use crate::options::{RunOptions, SolverMethod};
+
+/// First engine version that reads `transient.airflowMaxIterations` and
+/// `airflowRelaxFactor`; older engines retry without relaxing.
+pub const MIN_RELAX_ENGINE_VERSION: (u32, u32, u32) = (0, 4, 0);
+
+/// Airflow iterations per step of a relaxed retry; the engine's default is 100
+const RELAXED_MAX_ITERATIONS: u32 = 400;
+
+/// SUR relaxation factor of a relaxed retry; the engine's default is 0.75
+const RELAXED_RELAX_FACTOR: f64 = 0.5;
+
+/// Passed to `run_engine` to enable the retry.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    /// Also relax the airflow solve of a transient model on the retry: more
+    /// iterations per step and stronger under-relaxation, set through
+    /// `transient.airflowMaxIterations` and `airflowRelaxFactor`
+    pub relax_transient: bool,
+}
+
+/// One engine invocation of a retried run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolveAttempt {
+    pub method: SolverMethod,
+    /// Run with the relaxed transient airflow settings
+    pub relaxed: bool,
+    /// The steady solve, or every transient output step, converged
+    pub converged: bool,
+    /// Steady solver iterations, or the most any transient step needed
+    pub iterations: Option<u32>,
+    /// `solver.maxResidual` of steady output [kg/s]
+    pub max_residual: Option<f64>,
+    /// Why the attempt produced no result
+    pub error: Option<String>,
+    /// This attempt produced the returned result
+    pub selected: bool,
+}
+
+impl SolveAttempt {
+    fn new(outcome: &RunOutcome, method: SolverMethod, relaxed: bool) -> Self {
+        let (converged, iterations, max_residual, error) = match outcome {
+            RunOutcome::Completed { result, partial, .. } => {
+                (!partial && result.converged(), Some(result.iterations()), result.max_residual(), None)
+            }
+            RunOutcome::Failed { error } => (false, None, None, Some(error.to_string())),
+            RunOutcome::Cancelled => (false, None, None, Some("Cancelled".to_string())),
+        };
+        Self { method, relaxed, converged, iterations, max_residual, error, selected: false }
+    }
+
+    /// Ordering key: converged first, then lower residual. Attempts without
+    /// a result sort last.
+    fn rank(&self) -> (u8, f64) {
+        match (&self.error, self.converged) {
+            (Some(_), _) => (2, f64::INFINITY),
+            (None, true) => (0, 0.0),
+            (None, false) => (1, self.max_residual.filter(|r| !r.is_nan()).unwrap_or(f64::INFINITY)),
+        }
+    }
+}
+
+/// Whether an outcome is worth solving again with the other method. Steady
+/// output of an exit code 2 can still say `converged`, so `partial` counts too.
+pub fn not_converged(outcome: &RunOutcome) -> bool {
+    match outcome {
+        RunOutcome::Completed { result, partial, .. } => *partial || !result.converged(),
+        RunOutcome::Failed { error } => matches!(error, EngineError::NotConverged { .. }),
+        RunOutcome::Cancelled => false,
+    }
+}
+
+/// Run `attempt(index, input, options)` with the requested options and, if
+/// the solve does not converge, again with the other method. Runs writing an
+/// HDF5 file are not retried: the file would hold the last attempt rather
+/// than the selected one.
+pub fn run_with_retry<F>(policy: RetryPolicy, input: &str, options: &RunOptions, mut attempt: F) -> RunOutcome
+where
+    F: FnMut(usize, &str, &RunOptions) -> RunOutcome,
+{
+    let first = attempt(0, input, options);
+    let mut attempts = vec![SolveAttempt::new(&first, options.method, false)];
+    if !not_converged(&first) || options.hdf5_output.is_some() {
+        return select(vec![first], attempts);
+    }
+
+    let method = match options.method {
+        SolverMethod::TrustRegion => SolverMethod::SubRelaxation,
+        SolverMethod::SubRelaxation => SolverMethod::TrustRegion,
+    };
+    let relaxed_input = if policy.relax_transient { relaxed_transient(input) } else { None };
+    log::info!("Airflow solve did not converge with -m {}; retrying with -m {}", options.method.flag(), method.flag());
+
+    let retry_options = RunOptions { method, ..options.clone() };
+    let second = attempt(1, relaxed_input.as_deref().unwrap_or(input), &retry_options);
+    if matches!(second, RunOutcome::Cancelled) {
+        return second;
+    }
+    attempts.push(SolveAttempt::new(&second, method, relaxed_input.is_some()));
+    select(vec![first, second], attempts)
+}
+
+/// Return the best outcome with `attempts` attached and marked.
+fn select(mut outcomes: Vec<RunOutcome>, mut attempts: Vec<SolveAttempt>) -> RunOutcome {
+    let best = (0..attempts.len())
+        .min_by(|&a, &b| attempts[a].rank().partial_cmp(&attempts[b].rank()).unwrap())
+        .unwrap_or(0);
+    attempts[best].selected = true;
+    let mut outcome = outcomes.swap_remove(best);
+    if let RunOutcome::Completed { attempts: listed, .. } = &mut outcome {
+        *listed = attempts;
+    }
+    outcome
+}
+
+/// `input` with the relaxed airflow settings in its `transient` section;
+/// `None` for a steady model or input that is not a JSON object. The method
+/// itself is switched with `-m`, which the engine applies over
+/// `transient.airflowMethod`.
+fn relaxed_transient(input: &str) -> Option<String> {
+    let mut model: Value = serde_json::from_str(input).ok()?;
+    let transient = model.get_mut("transient")?.as_object_mut()?;
+    transient.insert("airflowMaxIterations".to_string(), RELAXED_MAX_ITERATIONS.into());
+    transient.insert("airflowRelaxFactor".to_string(), RELAXED_RELAX_FACTOR.into());
+    serde_json::to_string(&model).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::launch::RunRequest;
+    use crate::runner::MockRunner;
+    use crate::testing::{case_input, launcher, request, run, TestDir};
+    use crate::validation;
+
+    #[test]
+    fn retry_switches_solver_method() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let runner =
+            || MockRunner { converges_with: Some(SolverMethod::SubRelaxation), ..MockRunner::case("case01_3room") };
+
+        let outcome = run(&launcher, runner(), request(case_input("case01_3room")));
+        let RunOutcome::Completed { partial, attempts, .. } = &outcome else { panic!("{:?}", outcome) };
+        assert!(*partial && attempts.is_empty());
+
+        let retried = RunRequest {
+            retry: Some(RetryPolicy::default()),
+            history_quota: Some(u64::MAX),
+            ..request(case_input("case01_3room"))
+        };
+        let outcome = run(&launcher, runner(), retried);
+        let RunOutcome::Completed { partial, attempts, .. } = &outcome else { panic!("{:?}", outcome) };
+        assert!(!partial);
+        let summary: Vec<_> = attempts.iter().map(|a| (a.method, a.converged, a.selected)).collect();
+        assert_eq!(summary, [(SolverMethod::TrustRegion, false, false), (SolverMethod::SubRelaxation, true, true)]);
+        assert!(attempts.iter().all(|a| a.iterations.is_some() && a.max_residual.is_some() && !a.relaxed));
+        assert_eq!(launcher.history.list()[0].attempts.len(), 2);
+    }
+
+    #[test]
+    fn retry_keeps_a_converged_first_attempt() {
+        let dir = TestDir::new();
+        let retried = RunRequest { retry: Some(RetryPolicy::default()), ..request(case_input("case01_3room")) };
+        let outcome = run(&launcher(&dir), MockRunner::case("case01_3room"), retried);
+        let RunOutcome::Completed { attempts, .. } = &outcome else { panic!("{:?}", outcome) };
+        assert_eq!(attempts.len(), 1);
+        assert!(attempts[0].converged && attempts[0].selected);
+    }
+
+    #[test]
+    fn retry_can_relax_transient_method() {
+        let dir = TestDir::new();
+        let runner =
+            MockRunner { converges_with: Some(SolverMethod::SubRelaxation), ..MockRunner::case("case02_co2_source") };
+        let inputs = runner.inputs.clone();
+        let policy = RetryPolicy { relax_transient: true };
+        let retried = RunRequest { retry: Some(policy), ..request(case_input("case02_co2_source")) };
+        let outcome = run(&launcher(&dir), runner, retried);
+        let RunOutcome::Completed { attempts, .. } = &outcome else { panic!("{:?}", outcome) };
+        assert!(!attempts[0].relaxed);
+        assert!(attempts[1].relaxed && attempts[1].selected);
+
+        // Only the retry reaches the engine with the relaxed settings
+        let inputs: Vec<Value> = inputs.lock().unwrap().iter().map(|i| serde_json::from_str(i).unwrap()).collect();
+        assert_eq!(inputs.len(), 2);
+        assert!(inputs[0]["transient"].get("airflowMaxIterations").is_none());
+        assert_eq!(inputs[1]["transient"]["airflowMaxIterations"], 400);
+        assert_eq!(inputs[1]["transient"]["airflowRelaxFactor"], 0.5);
+        assert!(validation::validate_input(&inputs[1].to_string()).is_ok());
+    }
+
+    #[test]
+    fn retry_reports_both_failures() {
+        let dir = TestDir::new();
+        let runner = MockRunner { exit_code: 2, ..MockRunner::case("case01_3room") };
+        let retried = RunRequest { retry: Some(RetryPolicy::default()), ..request(case_input("case01_3room")) };
+        let outcome = run(&launcher(&dir), runner, retried);
+        let RunOutcome::Completed { partial, attempts, .. } = &outcome else { panic!("{:?}", outcome) };
+        assert!(*partial);
+        assert_eq!(attempts.iter().map(|a| a.selected).collect::<Vec<_>>(), [true, false]);
+    }
+}
@@ -0,0 +1,192 @@
+//! The engine as `launch` sees it, so runs can be tested without a real
+//! `contam_engine` binary.
+
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+
+use crate::discovery::EngineInfo;
+use crate::engine::{self, RunOutcome};
+use crate::limits::RunLimits;
+use crate::options::RunOptions;
+use crate::progress::Progress;
+use crate::workdir::RunFiles;
+
+pub type ProgressCallback = Box<dyn FnMut(Progress) + Send>;
+
+pub trait EngineRunner: Send + Sync {
+    /// Version and capabilities of the engine runs will use.
+    fn info(&self) -> EngineInfo;
+
+    /// Run the engine on `input` to completion, as `engine::execute` does.
+    fn run(
+        &self,
+        files: RunFiles,
+        input: &str,
+        options: &RunOptions,
+        limits: RunLimits,
+        cancel: &AtomicBool,
+        on_progress: ProgressCallback,
+    ) -> RunOutcome;
+}
+
+/// Spawns `contam_engine` for every run.
+pub struct ProcessRunner {
+    info: EngineInfo,
+    /// Builds a fresh command naming the engine program, once per run
+    command: Box<dyn Fn() -> Command + Send + Sync>,
+}
+
+impl ProcessRunner {
+    pub fn new(info: EngineInfo, command: impl Fn() -> Command + Send + Sync + 'static) -> Self {
+        Self { info, command: Box::new(command) }
+    }
+}
+
+impl EngineRunner for ProcessRunner {
+    fn info(&self) -> EngineInfo {
+        self.info.clone()
+    }
+
+    fn run(
+        &self,
+        files: RunFiles,
+        input: &str,
+        options: &RunOptions,
+        limits: RunLimits,
+        cancel: &AtomicBool,
+        on_progress: ProgressCallback,
+    ) -> RunOutcome {
+        engine::execute((self.command)(), files, input, options, limits, cancel, on_progress)
+    }
+}
+
+#[cfg(test)]
+pub use mock::MockRunner;
+
+#[cfg(test)]
+mod mock {
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use super::{EngineRunner, ProgressCallback};
+    use crate::discovery::{EngineInfo, EnginePath, EngineSource};
+    use crate::engine::{self, RunOutcome};
+    use crate::error::{EngineError, EngineLogs};
+    use crate::limits::RunLimits;
+    use crate::options::{RunOptions, SolverMethod};
+    use crate::progress::ProgressTracker;
+    use crate::workdir::RunFiles;
+
+    /// Stands in for the engine: "runs" for `duration`, writes the stored
+    /// output of a validation case if one is set, then exits with `exit_code`.
+    /// Timeouts and cancellation behave as with a real process.
+    pub struct MockRunner {
+        /// Directory under `validation/` whose `output.json` becomes the run's output
+        pub case: Option<&'static str>,
+        pub exit_code: i32,
+        /// Exit with 2 instead of `exit_code` unless run with this method
+        pub converges_with: Option<SolverMethod>,
+        /// Printed before exiting; progress lines are reported as with `-v`
+        pub stdout: String,
+        pub duration: Duration,
+        /// The input of every run, in order
+        pub inputs: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockRunner {
+        /// A run that reproduces `validation/<case>/output.json`.
+        pub fn case(case: &'static str) -> Self {
+            Self {
+                case: Some(case),
+                exit_code: 0,
+                converges_with: None,
+                stdout: String::new(),
+                duration: Duration::ZERO,
+                inputs: Arc::default(),
+            }
+        }
+
+        /// A run that writes no output and exits with `exit_code`.
+        pub fn exiting(exit_code: i32) -> Self {
+            Self {
+                case: None,
+                exit_code,
+                converges_with: None,
+                stdout: String::new(),
+                duration: Duration::ZERO,
+                inputs: Arc::default(),
+            }
+        }
+
+        /// What the mock reports as its engine, also usable for real test runners.
+        pub fn engine_info() -> EngineInfo {
+            EngineInfo {
+                location: EnginePath { path: "mock_engine".to_string(), source: EngineSource::Path },
+                found: true,
+                version: Some("0.4.0".to_string()),
+                hdf5: false,
+                compatible: true,
+                min_version: "0.2.0".to_string(),
+                error: None,
+            }
+        }
+
+        pub fn case_dir(case: &str) -> PathBuf {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../validation").join(case)
+        }
+    }
+
+    impl EngineRunner for MockRunner {
+        fn info(&self) -> EngineInfo {
+            Self::engine_info()
+        }
+
+        fn run(
+            &self,
+            files: RunFiles,
+            input: &str,
+            options: &RunOptions,
+            limits: RunLimits,
+            cancel: &AtomicBool,
+            mut on_progress: ProgressCallback,
+        ) -> RunOutcome {
+            self.inputs.lock().unwrap().push(input.to_string());
+            if let Err(e) = files.write_input(input) {
+                return RunOutcome::Failed { error: EngineError::io(format!("Failed to write input file: {}", e)) };
+            }
+            let started = Instant::now();
+            while started.elapsed() < self.duration {
+                if cancel.load(Ordering::SeqCst) {
+                    return RunOutcome::Cancelled;
+                }
+                if let Some(timeout) = limits.timeout().filter(|&t| started.elapsed() >= t) {
+                    let logs = EngineLogs::default();
+                    return RunOutcome::Failed {
+                        error: EngineError::TimedOut { timeout_secs: timeout.as_secs(), logs },
+                    };
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+
+            let mut tracker = ProgressTracker::new();
+            for line in self.stdout.lines() {
+                if let Some(progress) = tracker.feed(line) {
+                    on_progress(progress);
+                }
+            }
+            if let Some(case) = self.case {
+                if let Err(e) = std::fs::copy(Self::case_dir(case).join("output.json"), &files.output) {
+                    return RunOutcome::Failed { error: EngineError::io(format!("Failed to copy {}: {}", case, e)) };
+                }
+            }
+            let exit_code = match self.converges_with {
+                Some(method) if method != options.method => 2,
+                _ => self.exit_code,
+            };
+            let logs = EngineLogs { stdout: self.stdout.clone(), stderr: String::new(), exit_code: Some(exit_code) };
+            engine::exit_outcome(Some(exit_code), &files, logs)
+        }
+    }
+}
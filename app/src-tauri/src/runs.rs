@@ -0,0 +1,480 @@
+//! Job manager for engine runs: a FIFO queue drained by at most
+//! `max_concurrent` worker threads, plus a shared view of every run's state.
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::AtomicBool;
+#[cfg(any(feature = "gui", test))]
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::RunOutcome;
+use crate::error::EngineError;
+use crate::progress::Progress;
+
+/// Finished runs kept for `list_runs`/`wait_run` before the oldest are dropped.
+const MAX_FINISHED_RUNS: usize = 50;
+
+/// Work for one run: spawn the engine, wait for it while watching the
+/// cancellation flag, clean up, and return the outcome.
+pub type Job = Box<dyn FnOnce(&AtomicBool) -> RunOutcome + Send>;
+
+/// Called once for every run that leaves the queue or finishes.
+pub type FinishedHook = Box<dyn Fn(&str, &RunOutcome) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    TimedOut,
+    OutOfMemory,
+}
+
+impl From<&RunOutcome> for RunState {
+    fn from(outcome: &RunOutcome) -> Self {
+        match outcome {
+            RunOutcome::Completed { .. } => RunState::Completed,
+            RunOutcome::Failed { error: EngineError::TimedOut { .. } } => RunState::TimedOut,
+            RunOutcome::Failed { error: EngineError::OutOfMemory { .. } } => RunState::OutOfMemory,
+            RunOutcome::Failed { .. } => RunState::Failed,
+            RunOutcome::Cancelled => RunState::Cancelled,
+        }
+    }
+}
+
+/// Lightweight snapshot of a run, without its output.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunStatus {
+    pub run_id: String,
+    pub state: RunState,
+    /// Unix timestamps [ms]
+    pub queued_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub progress: Option<Progress>,
+}
+
+struct RunEntry {
+    status: RunStatus,
+    cancel: Arc<AtomicBool>,
+    job: Option<Job>,
+    outcome: Option<RunOutcome>,
+}
+
+struct Inner {
+    runs: HashMap<String, RunEntry>,
+    queue: VecDeque<String>,
+    finished: VecDeque<String>,
+    running: usize,
+    max_concurrent: usize,
+}
+
+struct Shared {
+    inner: Mutex<Inner>,
+    changed: Condvar,
+    on_finished: FinishedHook,
+}
+
+/// Cheap to clone; all clones share the same queue.
+#[derive(Clone)]
+pub struct JobManager {
+    shared: Arc<Shared>,
+}
+
+impl JobManager {
+    /// `max_concurrent == 0` means one run per available CPU.
+    pub fn new(max_concurrent: usize, on_finished: FinishedHook) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                inner: Mutex::new(Inner {
+                    runs: HashMap::new(),
+                    queue: VecDeque::new(),
+                    finished: VecDeque::new(),
+                    running: 0,
+                    max_concurrent: resolve_concurrency(max_concurrent),
+                }),
+                changed: Condvar::new(),
+                on_finished,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.shared.inner.lock().unwrap()
+    }
+
+    /// Change the concurrency limit. Extra slots are filled immediately;
+    /// runs already in flight are never interrupted.
+    #[cfg(any(feature = "gui", test))]
+    pub fn set_max_concurrent(&self, max_concurrent: usize) {
+        self.lock().max_concurrent = resolve_concurrency(max_concurrent);
+        self.dispatch();
+    }
+
+    /// Queue a run and start it right away if a slot is free.
+    pub fn submit(&self, run_id: &str, job: Job) {
+        {
+            let mut inner = self.lock();
+            inner.runs.insert(
+                run_id.to_string(),
+                RunEntry {
+                    status: RunStatus {
+                        run_id: run_id.to_string(),
+                        state: RunState::Queued,
+                        queued_at: now_ms(),
+                        started_at: None,
+                        finished_at: None,
+                        progress: None,
+                    },
+                    cancel: Arc::new(AtomicBool::new(false)),
+                    job: Some(job),
+                    outcome: None,
+                },
+            );
+            inner.queue.push_back(run_id.to_string());
+        }
+        self.shared.changed.notify_all();
+        self.dispatch();
+    }
+
+    /// Record a run that needs no worker, such as one served from the cache,
+    /// and report it as finished right away.
+    pub fn complete(&self, run_id: &str, outcome: RunOutcome) {
+        let now = now_ms();
+        self.lock().runs.insert(
+            run_id.to_string(),
+            RunEntry {
+                status: RunStatus {
+                    run_id: run_id.to_string(),
+                    state: RunState::Running,
+                    queued_at: now,
+                    started_at: Some(now),
+                    finished_at: None,
+                    progress: None,
+                },
+                cancel: Arc::new(AtomicBool::new(false)),
+                job: None,
+                outcome: None,
+            },
+        );
+        self.finish(run_id, outcome);
+    }
+
+    /// Start queued runs while there are free slots.
+    fn dispatch(&self) {
+        loop {
+            let (run_id, job, cancel) = {
+                let mut inner = self.lock();
+                if inner.running >= inner.max_concurrent {
+                    return;
+                }
+                let Some(run_id) = inner.queue.pop_front() else { return };
+                inner.running += 1;
+                let entry = inner.runs.get_mut(&run_id).expect("queued run has an entry");
+                entry.status.state = RunState::Running;
+                entry.status.started_at = Some(now_ms());
+                let job = entry.job.take().expect("queued run has a job");
+                (run_id, job, entry.cancel.clone())
+            };
+            self.shared.changed.notify_all();
+
+            let manager = self.clone();
+            std::thread::spawn(move || {
+                // A panicking job must still free its slot and finish its run,
+                // or `wait` blocks forever and the queue stalls
+                let outcome = panic::catch_unwind(AssertUnwindSafe(|| job(&cancel))).unwrap_or_else(|payload| {
+                    RunOutcome::Failed { error: EngineError::io(format!("Run panicked: {}", panic_message(&*payload))) }
+                });
+                manager.lock().running -= 1;
+                manager.finish(&run_id, outcome);
+                manager.dispatch();
+            });
+        }
+    }
+
+    fn finish(&self, run_id: &str, outcome: RunOutcome) {
+        {
+            let mut inner = self.lock();
+            if let Some(entry) = inner.runs.get_mut(run_id) {
+                entry.status.state = RunState::from(&outcome);
+                entry.status.finished_at = Some(now_ms());
+                entry.outcome = Some(outcome.clone());
+            }
+            inner.finished.push_back(run_id.to_string());
+            while inner.finished.len() > MAX_FINISHED_RUNS {
+                if let Some(old) = inner.finished.pop_front() {
+                    inner.runs.remove(&old);
+                }
+            }
+        }
+        self.shared.changed.notify_all();
+        (self.shared.on_finished)(run_id, &outcome);
+    }
+
+    /// Record the latest progress of a running job.
+    pub fn set_progress(&self, run_id: &str, progress: Progress) {
+        if let Some(entry) = self.lock().runs.get_mut(run_id) {
+            entry.status.progress = Some(progress);
+        }
+    }
+
+    /// Cancel a queued or running run. Returns false if the run is unknown or
+    /// has already finished.
+    #[cfg(any(feature = "gui", test))]
+    pub fn cancel(&self, run_id: &str) -> bool {
+        let dequeued = {
+            let mut inner = self.lock();
+            let Some(entry) = inner.runs.get(run_id) else { return false };
+            match entry.status.state {
+                RunState::Running => {
+                    entry.cancel.store(true, Ordering::SeqCst);
+                    return true;
+                }
+                RunState::Queued => {
+                    inner.queue.retain(|id| id != run_id);
+                    true
+                }
+                _ => false,
+            }
+        };
+        if dequeued {
+            self.finish(run_id, RunOutcome::Cancelled);
+        }
+        dequeued
+    }
+
+    pub fn status(&self, run_id: &str) -> Option<RunStatus> {
+        self.lock().runs.get(run_id).map(|e| e.status.clone())
+    }
+
+    /// All known runs, oldest first.
+    #[cfg(any(feature = "gui", test))]
+    pub fn list(&self) -> Vec<RunStatus> {
+        let mut runs: Vec<RunStatus> = self.lock().runs.values().map(|e| e.status.clone()).collect();
+        runs.sort_by_key(|r| r.queued_at);
+        runs
+    }
+
+    /// Block until the run finishes (or `timeout` elapses) and return its
+    /// outcome. `Ok(None)` means the timeout expired first.
+    pub fn wait(&self, run_id: &str, timeout: Option<Duration>) -> Result<Option<RunOutcome>, String> {
+        let deadline = timeout.map(|t| std::time::Instant::now() + t);
+        let mut inner = self.lock();
+        loop {
+            let entry = inner.runs.get(run_id).ok_or_else(|| format!("No run with ID {}", run_id))?;
+            if let Some(outcome) = &entry.outcome {
+                return Ok(Some(outcome.clone()));
+            }
+            inner = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(None);
+                    }
+                    self.shared.changed.wait_timeout(inner, remaining).unwrap().0
+                }
+                None => self.shared.changed.wait(inner).unwrap(),
+            };
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown cause"
+    }
+}
+
+fn resolve_concurrency(max_concurrent: usize) -> usize {
+    if max_concurrent > 0 {
+        max_concurrent
+    } else {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    }
+}
+
+/// Current Unix time [ms].
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    use super::*;
+
+    const SHORT: Duration = Duration::from_millis(100);
+    const LONG: Duration = Duration::from_secs(5);
+
+    fn manager(max_concurrent: usize) -> JobManager {
+        JobManager::new(max_concurrent, Box::new(|_, _| {}))
+    }
+
+    /// A job that reports `name` on `started` and then blocks until the returned
+    /// sender fires or the run is cancelled.
+    fn blocking(name: &'static str, started: &Sender<&'static str>) -> (Job, Sender<()>) {
+        let (release, released) = mpsc::channel::<()>();
+        let started = started.clone();
+        let job: Job = Box::new(move |cancel: &AtomicBool| {
+            started.send(name).unwrap();
+            loop {
+                if cancel.load(Ordering::SeqCst) {
+                    return RunOutcome::Cancelled;
+                }
+                if released.recv_timeout(Duration::from_millis(5)).is_ok() {
+                    return RunOutcome::Failed { error: EngineError::io(format!("{} released", name)) };
+                }
+            }
+        });
+        (job, release)
+    }
+
+    fn next_started(started: &Receiver<&'static str>) -> &'static str {
+        started.recv_timeout(LONG).expect("a job starts")
+    }
+
+    fn state(jobs: &JobManager, run_id: &str) -> RunState {
+        jobs.status(run_id).expect("run is known").state
+    }
+
+    #[test]
+    fn runs_start_in_order_up_to_the_limit() {
+        let jobs = manager(2);
+        let (started_tx, started) = mpsc::channel();
+        let mut releases = HashMap::new();
+        for name in ["a", "b", "c", "d"] {
+            let (job, release) = blocking(name, &started_tx);
+            jobs.submit(name, job);
+            releases.insert(name, release);
+        }
+
+        let mut first = [next_started(&started), next_started(&started)];
+        first.sort_unstable();
+        assert_eq!(first, ["a", "b"]);
+        assert!(started.recv_timeout(SHORT).is_err(), "only two runs at a time");
+        assert_eq!(state(&jobs, "c"), RunState::Queued);
+        assert_eq!(state(&jobs, "d"), RunState::Queued);
+
+        releases["a"].send(()).unwrap();
+        assert_eq!(next_started(&started), "c");
+        releases["b"].send(()).unwrap();
+        assert_eq!(next_started(&started), "d");
+        assert!(matches!(jobs.wait("a", Some(LONG)), Ok(Some(RunOutcome::Failed { .. }))));
+        assert_eq!(state(&jobs, "a"), RunState::Failed);
+    }
+
+    #[test]
+    fn cancelling_a_queued_run_never_starts_it() {
+        let jobs = manager(1);
+        let (started_tx, started) = mpsc::channel();
+        let (a, release_a) = blocking("a", &started_tx);
+        let (b, _release_b) = blocking("b", &started_tx);
+        jobs.submit("a", a);
+        jobs.submit("b", b);
+        assert_eq!(next_started(&started), "a");
+
+        assert!(jobs.cancel("b"));
+        assert_eq!(state(&jobs, "b"), RunState::Cancelled);
+        assert!(matches!(jobs.wait("b", None), Ok(Some(RunOutcome::Cancelled))));
+        assert!(!jobs.cancel("b"), "already finished");
+
+        release_a.send(()).unwrap();
+        jobs.wait("a", Some(LONG)).unwrap().expect("a finishes");
+        assert!(started.recv_timeout(SHORT).is_err(), "b was dequeued");
+        assert!(!jobs.cancel("unknown"));
+    }
+
+    #[test]
+    fn cancelling_a_running_run_signals_its_job() {
+        let jobs = manager(1);
+        let (started_tx, started) = mpsc::channel();
+        let (a, _release_a) = blocking("a", &started_tx);
+        let (b, _release_b) = blocking("b", &started_tx);
+        jobs.submit("a", a);
+        jobs.submit("b", b);
+        assert_eq!(next_started(&started), "a");
+
+        assert!(jobs.cancel("a"));
+        assert!(matches!(jobs.wait("a", Some(LONG)), Ok(Some(RunOutcome::Cancelled))));
+        assert_eq!(state(&jobs, "a"), RunState::Cancelled);
+        assert_eq!(next_started(&started), "b", "the freed slot goes to the next run");
+    }
+
+    #[test]
+    fn raising_the_limit_starts_queued_runs() {
+        let jobs = manager(1);
+        let (started_tx, started) = mpsc::channel();
+        let (a, _release_a) = blocking("a", &started_tx);
+        let (b, _release_b) = blocking("b", &started_tx);
+        jobs.submit("a", a);
+        jobs.submit("b", b);
+        assert_eq!(next_started(&started), "a");
+        assert!(started.recv_timeout(SHORT).is_err());
+
+        jobs.set_max_concurrent(2);
+        assert_eq!(next_started(&started), "b");
+        assert_eq!(state(&jobs, "a"), RunState::Running, "runs in flight are left alone");
+        jobs.cancel("a");
+        jobs.cancel("b");
+    }
+
+    #[test]
+    fn wait_returns_none_on_timeout_and_the_outcome_once_finished() {
+        let jobs = manager(1);
+        let (started_tx, started) = mpsc::channel();
+        let (a, release_a) = blocking("a", &started_tx);
+        jobs.submit("a", a);
+        next_started(&started);
+
+        assert!(matches!(jobs.wait("a", Some(SHORT)), Ok(None)));
+        release_a.send(()).unwrap();
+        assert!(matches!(jobs.wait("a", None), Ok(Some(RunOutcome::Failed { .. }))));
+        assert!(jobs.wait("unknown", None).is_err());
+    }
+
+    #[test]
+    fn a_panicking_job_fails_its_run_and_frees_its_slot() {
+        let jobs = manager(1);
+        let (started_tx, started) = mpsc::channel();
+        let (b, _release_b) = blocking("b", &started_tx);
+        jobs.submit("a", Box::new(|_: &AtomicBool| panic!("engine supervisor bug")));
+        jobs.submit("b", b);
+
+        let outcome = jobs.wait("a", Some(LONG)).unwrap().expect("a finishes");
+        let RunOutcome::Failed { error } = outcome else { panic!("{:?}", outcome) };
+        assert!(error.to_string().contains("engine supervisor bug"), "{}", error);
+        assert_eq!(state(&jobs, "a"), RunState::Failed);
+        assert_eq!(next_started(&started), "b");
+        jobs.cancel("b");
+    }
+
+    #[test]
+    fn oldest_finished_runs_are_evicted() {
+        let finished = Arc::new(Mutex::new(Vec::new()));
+        let reported = finished.clone();
+        let jobs = JobManager::new(1, Box::new(move |run_id, _| reported.lock().unwrap().push(run_id.to_string())));
+        for i in 0..=MAX_FINISHED_RUNS {
+            jobs.complete(&format!("run{}", i), RunOutcome::Cancelled);
+        }
+
+        assert!(jobs.status("run0").is_none());
+        assert!(jobs.wait("run0", Some(SHORT)).is_err());
+        assert_eq!(state(&jobs, "run1"), RunState::Cancelled);
+        assert_eq!(jobs.list().len(), MAX_FINISHED_RUNS);
+        assert_eq!(finished.lock().unwrap().len(), MAX_FINISHED_RUNS + 1, "every run is reported once");
+    }
+}
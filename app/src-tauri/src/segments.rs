@@ -0,0 +1,397 @@
+//! Segmented transient runs: `transient.startTime..endTime` split into
+//! windows that run one after another, each seeded with the final node
+//! concentrations (`initialConcentrations`) and pressures (node `pressure`)
+//! of the one before, and stitched into one result.
+//!
+//! Every finished segment is checkpointed, so running the same request again
+//! resumes after the last one. A cancelled or failed run returns the
+//! segments finished so far as a partial result. Controller states and
+//! occupant positions are not carried over; they start afresh in each segment.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::engine::RunOutcome;
+use crate::error::{EngineError, EngineLogs};
+use crate::progress::Progress;
+use crate::results::{EngineResult, TransientResult, TransientStitcher};
+use crate::summary::RunSummary;
+use crate::warm_start;
+use crate::workdir;
+
+/// First engine version that reads `initialConcentrations`; older engines
+/// run the model in one piece.
+pub const MIN_ENGINE_VERSION: (u32, u32, u32) = (0, 3, 0);
+
+/// Passed to `run_engine` to split a transient run.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentPolicy {
+    /// Simulated time per segment [s], rounded up to whole output intervals
+    pub length: f64,
+}
+
+/// The simulation windows of a segmented run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    /// Segment length after rounding [s]
+    pub length: f64,
+    /// `(startTime, endTime)` of each segment [s]
+    pub windows: Vec<(f64, f64)>,
+}
+
+impl Plan {
+    /// Split the `transient` window of `input`, with `JsonReader`'s defaults
+    /// for missing times. `None` for models without a `transient` section,
+    /// windows that fit in one segment, and non-positive lengths.
+    pub fn new(input: &str, policy: SegmentPolicy) -> Option<Self> {
+        let model: Value = serde_json::from_str(input).ok()?;
+        let transient = model.get("transient")?.as_object()?;
+        let time = |key: &str, default: f64| transient.get(key).and_then(Value::as_f64).unwrap_or(default);
+        let (start, end, interval) = (time("startTime", 0.0), time("endTime", 3600.0), time("outputInterval", 60.0));
+        if !(policy.length > 0.0 && interval > 0.0 && end > start) {
+            return None;
+        }
+        // Whole output intervals keep the output times of an unsegmented run
+        let length = (policy.length / interval).ceil() * interval;
+        let count = ((end - start) / length - 1e-9).ceil() as usize;
+        if count < 2 {
+            return None;
+        }
+        let windows = (0..count)
+            .map(|k| (start + k as f64 * length, (start + (k + 1) as f64 * length).min(end)))
+            .collect();
+        Some(Self { length, windows })
+    }
+
+    /// Progress scaling for a run of this plan starting now.
+    pub fn window(&self) -> Window {
+        let start = self.windows.first().map_or(0.0, |w| w.0);
+        let end = self.windows.last().map_or(0.0, |w| w.1);
+        Window { start, end, started: Instant::now() }
+    }
+}
+
+/// Reports a segment's progress as progress through the whole run.
+#[derive(Debug, Clone, Copy)]
+pub struct Window {
+    start: f64,
+    end: f64,
+    started: Instant,
+}
+
+impl Window {
+    pub fn progress(&self, segment: Progress) -> Progress {
+        let span = self.end - self.start;
+        let fraction = if span > 0.0 { ((segment.time - self.start) / span).clamp(0.0, 1.0) } else { 1.0 };
+        let elapsed = self.started.elapsed().as_secs_f64();
+        Progress {
+            time: segment.time,
+            end_time: self.end,
+            percent: fraction * 100.0,
+            eta_seconds: (fraction > 0.0).then(|| elapsed * (1.0 - fraction) / fraction),
+        }
+    }
+}
+
+/// Reported as each segment finishes or is read back from its checkpoint.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishedSegment {
+    pub index: usize,
+    pub count: usize,
+    /// [s]
+    pub start_time: f64,
+    pub end_time: f64,
+    /// Read from a checkpoint of an earlier attempt rather than run
+    pub resumed: bool,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    pub result: Arc<EngineResult>,
+}
+
+/// Run the segments of `plan` with `run(index, input)` and stitch their
+/// results. Finished segments are saved in `checkpoints`, and segments found
+/// there are not run again; the directory is removed once the run completes.
+pub fn run_segmented<R, S>(
+    plan: &Plan,
+    input: &str,
+    checkpoints: Option<&Path>,
+    spill_dir: &Path,
+    mut run: R,
+    mut on_segment: S,
+) -> RunOutcome
+where
+    R: FnMut(usize, &str) -> RunOutcome,
+    S: FnMut(&FinishedSegment),
+{
+    let mut model: Value = match serde_json::from_str(input) {
+        Ok(model) => model,
+        Err(e) => return failed(format!("Failed to parse input: {}", e)),
+    };
+    let mut stitcher = TransientStitcher::new(Some(spill_dir));
+    let mut logs = EngineLogs::default();
+    let count = plan.windows.len();
+    let mut completed = true;
+    let mut stopped = None;
+
+    for (index, &(start, end)) in plan.windows.iter().enumerate() {
+        set_window(&mut model, start, end);
+        let checkpoint = checkpoints.map(|dir| dir.join(format!("segment-{}.json", index)));
+        let (result, resumed, partial) =
+            match checkpoint.as_deref().and_then(|path| load_checkpoint(path, start, end, spill_dir)) {
+                Some(result) => (result, true, false),
+                None => match run(index, &model.to_string()) {
+                    RunOutcome::Completed { result, partial, logs: segment_logs, .. } => {
+                        append_logs(&mut logs, segment_logs);
+                        (result, false, partial)
+                    }
+                    outcome => {
+                        stopped = Some(outcome);
+                        break;
+                    }
+                },
+            };
+        let EngineResult::Transient(transient) = &*result else {
+            return failed(format!("Segment {} produced steady output", index + 1));
+        };
+        if let Err(e) = stitcher.push(transient) {
+            return failed(e);
+        }
+        if let Some(path) = checkpoint.filter(|_| !resumed && !partial) {
+            if let Err(e) = save_checkpoint(&path, &result) {
+                log::warn!("Failed to checkpoint segment {}: {}", index + 1, e);
+            }
+        }
+        let segment = FinishedSegment {
+            index,
+            count,
+            start_time: start,
+            end_time: end,
+            resumed,
+            #[cfg(feature = "gui")]
+            result: result.clone(),
+        };
+        on_segment(&segment);
+        if partial {
+            completed = false;
+            break;
+        }
+        seed(&mut model, transient, &result);
+    }
+
+    if let Some(outcome) = stopped {
+        if stitcher.step_count() == 0 {
+            return outcome;
+        }
+        match &outcome {
+            RunOutcome::Failed { error } => log::warn!("Segmented run stopped early: {}", error),
+            _ => log::info!("Segmented run cancelled; keeping the finished segments"),
+        }
+        completed = false;
+    }
+    let result = match stitcher.finish(completed) {
+        Ok(result) => result,
+        Err(e) => return failed(e),
+    };
+    if let Some(dir) = checkpoints.filter(|_| completed) {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    let mut summary = RunSummary::parse(&logs.stdout);
+    summary.start_time = plan.windows.first().map(|w| w.0);
+    summary.end_time = plan.windows.last().map(|w| w.1);
+    summary.output_steps = Some(result.step_count());
+    RunOutcome::Completed {
+        result: Arc::new(EngineResult::Transient(result)),
+        partial: !completed,
+        max_residual: None,
+        summary,
+        logs,
+        cached_run_id: None,
+        attempts: Vec::new(),
+    }
+}
+
+fn failed(message: String) -> RunOutcome {
+    RunOutcome::Failed { error: EngineError::io(message) }
+}
+
+fn set_window(model: &mut Value, start: f64, end: f64) {
+    if let Some(transient) = model.get_mut("transient").and_then(Value::as_object_mut) {
+        transient.insert("startTime".to_string(), start.into());
+        transient.insert("endTime".to_string(), end.into());
+    }
+}
+
+/// Start the next segment from the state `result` ended in. Ambient nodes
+/// keep their boundary pressure and outdoor concentrations.
+fn seed(model: &mut Value, transient: &TransientResult, result: &EngineResult) {
+    warm_start::set_pressures(model, &result.final_pressures());
+    let concentrations = transient.final_concentrations();
+    let Some(nodes) = model.get_mut("nodes").and_then(Value::as_array_mut) else { return };
+    for node in nodes {
+        if node["type"].as_str() == Some("ambient") {
+            continue;
+        }
+        let id = node["id"].as_i64().and_then(|id| i32::try_from(id).ok());
+        let Some(by_species) = id.and_then(|id| concentrations.get(&id)) else { continue };
+        let initial: Map<String, Value> = by_species.iter().map(|(s, &c)| (s.to_string(), c.into())).collect();
+        if let Some(node) = node.as_object_mut() {
+            node.insert("initialConcentrations".to_string(), initial.into());
+        }
+    }
+}
+
+fn append_logs(logs: &mut EngineLogs, segment: EngineLogs) {
+    logs.stdout.push_str(&segment.stdout);
+    logs.stderr.push_str(&segment.stderr);
+    logs.exit_code = segment.exit_code;
+}
+
+/// A complete segment output covering `start..end`, if one was saved.
+fn load_checkpoint(path: &Path, start: f64, end: f64, spill_dir: &Path) -> Option<Arc<EngineResult>> {
+    let file = File::open(path).ok()?;
+    let result = EngineResult::from_reader(BufReader::new(file), Some(spill_dir)).ok()?;
+    let EngineResult::Transient(transient) = &result else { return None };
+    let same = |a: f64, b: f64| (a - b).abs() <= 1e-6 * b.abs().max(1.0);
+    let times = transient.times();
+    let covers = times.first().is_some_and(|&t| same(t, start)) && times.last().is_some_and(|&t| same(t, end));
+    (transient.completed && covers).then(|| Arc::new(result))
+}
+
+/// Write `result` in the engine's output layout, replacing `path` only once complete.
+fn save_checkpoint(path: &Path, result: &EngineResult) -> Result<(), String> {
+    let dir = path.parent().ok_or("Checkpoint path has no directory")?;
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let partial = path.with_extension("json.part");
+    let file = workdir::create_private(&partial).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, result).map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())?;
+    drop(writer);
+    std::fs::rename(&partial, path).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::discovery::EngineInfo;
+    use crate::engine;
+    use crate::launch::RunRequest;
+    use crate::limits::RunLimits;
+    use crate::options::RunOptions;
+    use crate::runner::{EngineRunner, MockRunner, ProgressCallback};
+    use crate::testing::{assert_case_output, case_input, case_output, launcher, request, run, TestDir};
+    use crate::workdir::RunFiles;
+
+    /// Answers each segment with the steps of `case02_co2_source` inside its
+    /// window, so stitched segments reproduce the whole output.
+    #[derive(Clone, Default)]
+    struct WindowRunner {
+        /// Inputs the engine was run on
+        inputs: Arc<Mutex<Vec<Value>>>,
+        /// Cancel segments starting at or after this time [s]
+        cancel_from: Option<f64>,
+    }
+
+    impl EngineRunner for WindowRunner {
+        fn info(&self) -> EngineInfo {
+            MockRunner::engine_info()
+        }
+
+        fn run(
+            &self,
+            files: RunFiles,
+            input: &str,
+            _: &RunOptions,
+            _: RunLimits,
+            _: &AtomicBool,
+            _: ProgressCallback,
+        ) -> RunOutcome {
+            let input: Value = serde_json::from_str(input).unwrap();
+            let (start, end) = window(&input);
+            self.inputs.lock().unwrap().push(input);
+            if self.cancel_from.is_some_and(|t| start >= t) {
+                return RunOutcome::Cancelled;
+            }
+            let mut output = case_output("case02_co2_source");
+            output["timeSeries"].as_array_mut().unwrap().retain(|step| {
+                let time = step["time"].as_f64().unwrap();
+                time >= start && time <= end
+            });
+            std::fs::write(&files.output, output.to_string()).unwrap();
+            engine::exit_outcome(Some(0), &files, EngineLogs { exit_code: Some(0), ..EngineLogs::default() })
+        }
+    }
+
+    /// `(startTime, endTime)` of a transient input
+    fn window(input: &Value) -> (f64, f64) {
+        (input["transient"]["startTime"].as_f64().unwrap(), input["transient"]["endTime"].as_f64().unwrap())
+    }
+
+    #[test]
+    fn segments_are_seeded_and_stitched() {
+        let dir = TestDir::new();
+        let runner = WindowRunner::default();
+        let segmented = RunRequest {
+            segments: Some(SegmentPolicy { length: 1000.0 }),
+            history_quota: Some(u64::MAX),
+            ..request(case_input("case02_co2_source"))
+        };
+        let outcome = run(&launcher(&dir), runner.clone(), segmented);
+        assert_case_output(&outcome, "case02_co2_source", false);
+        let RunOutcome::Completed { summary, .. } = &outcome else { unreachable!() };
+        assert_eq!((summary.start_time, summary.end_time, summary.output_steps), (Some(0.0), Some(3600.0), Some(61)));
+
+        // Rounded up to whole output intervals
+        let inputs = runner.inputs.lock().unwrap();
+        let windows: Vec<_> = inputs.iter().map(window).collect();
+        assert_eq!(windows, [(0.0, 1020.0), (1020.0, 2040.0), (2040.0, 3060.0), (3060.0, 3600.0)]);
+
+        let expected = case_output("case02_co2_source");
+        let step = &expected["timeSeries"][17];
+        assert_eq!(step["time"].as_f64(), Some(1020.0));
+        let second = &inputs[1]["nodes"];
+        assert_eq!(second[0].get("initialConcentrations"), None, "ambient nodes keep outdoor concentrations");
+        assert_eq!(second[1]["initialConcentrations"]["0"], step["concentrations"][1][0]);
+        assert_eq!(second[1]["pressure"], step["airflow"]["pressures"][1]);
+    }
+
+    #[test]
+    fn cancelled_segmented_runs_keep_finished_segments_and_resume() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let segmented = || RunRequest {
+            segments: Some(SegmentPolicy { length: 900.0 }),
+            ..request(case_input("case02_co2_source"))
+        };
+
+        let cancelling = WindowRunner { cancel_from: Some(1800.0), ..WindowRunner::default() };
+        let outcome = run(&launcher, cancelling, segmented());
+        let RunOutcome::Completed { result, partial, .. } = &outcome else { panic!("{:?}", outcome) };
+        let EngineResult::Transient(transient) = &**result else { panic!("steady output") };
+        assert!(*partial && !transient.completed);
+        assert_eq!(transient.times().last(), Some(&1800.0));
+
+        let runner = WindowRunner::default();
+        let outcome = run(&launcher, runner.clone(), segmented());
+        assert_case_output(&outcome, "case02_co2_source", false);
+        let started: Vec<_> = runner.inputs.lock().unwrap().iter().map(|input| window(input).0).collect();
+        assert_eq!(started, [1800.0, 2700.0], "finished segments are read from their checkpoints");
+
+        // Nothing to resume from once complete
+        let runner = WindowRunner::default();
+        run(&launcher, runner.clone(), segmented());
+        assert_eq!(runner.inputs.lock().unwrap().len(), 4);
+    }
+}
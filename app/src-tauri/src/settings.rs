@@ -0,0 +1,127 @@
+//! User-overridable backend settings, persisted as JSON in the app config directory.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::limits::RunLimits;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Built-in wall-clock limit for one engine run: long enough for annual transient runs.
+const DEFAULT_RUN_TIMEOUT_SECS: u64 = 6 * 60 * 60;
+/// Built-in address-space cap for the engine process.
+const DEFAULT_MEMORY_LIMIT_MB: u64 = 8 * 1024;
+/// Built-in age after which leftover run files are swept at startup.
+const DEFAULT_TEMP_MAX_AGE_HOURS: u64 = 24;
+/// Built-in disk quota for stored run history.
+const DEFAULT_HISTORY_QUOTA_MB: u64 = 2 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    /// Wall-clock limit for one engine run [s]; 0 disables it
+    pub run_timeout_secs: u64,
+    /// Memory cap for the engine process [MiB]; 0 disables it
+    pub memory_limit_mb: u64,
+    /// Engine runs allowed in parallel; 0 means one per CPU
+    pub max_concurrent_runs: usize,
+    /// Leftover run files older than this are removed at startup [h]
+    pub temp_max_age_hours: u64,
+    /// Oldest history runs are deleted once the history exceeds this [MiB]; 0 keeps no history
+    pub history_quota_mb: u64,
+    /// Explicit `contam_engine` location; `AIRSIM_ENGINE_PATH` takes precedence
+    pub engine_path: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            run_timeout_secs: DEFAULT_RUN_TIMEOUT_SECS,
+            memory_limit_mb: DEFAULT_MEMORY_LIMIT_MB,
+            max_concurrent_runs: 0,
+            temp_max_age_hours: DEFAULT_TEMP_MAX_AGE_HOURS,
+            history_quota_mb: DEFAULT_HISTORY_QUOTA_MB,
+            engine_path: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Limits applied to runs that do not specify their own.
+    pub fn run_limits(&self) -> RunLimits {
+        RunLimits {
+            timeout_secs: Some(self.run_timeout_secs),
+            memory_limit_mb: Some(self.memory_limit_mb),
+        }
+    }
+
+    pub fn history_quota_bytes(&self) -> u64 {
+        self.history_quota_mb * 1024 * 1024
+    }
+}
+
+/// Settings shared across commands, written back to disk on every update.
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    /// Load settings from `config_dir`, falling back to defaults when the file
+    /// is missing or unreadable.
+    pub fn load(config_dir: PathBuf) -> Self {
+        let path = config_dir.join(SETTINGS_FILE);
+        let settings = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self { path, settings: Mutex::new(settings) }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, settings: Settings) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        std::fs::write(&self.path, json)
+            .map_err(|e| format!("Failed to write settings file: {}", e))?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestDir;
+
+    #[test]
+    fn saved_settings_are_loaded_again() {
+        let dir = TestDir::new();
+        let config = dir.0.join("config");
+        assert_eq!(SettingsStore::load(config.clone()).get().run_timeout_secs, DEFAULT_RUN_TIMEOUT_SECS);
+
+        let store = SettingsStore::load(config.clone());
+        let engine_path = Some("/opt/engine".to_string());
+        let settings = Settings { max_concurrent_runs: 3, engine_path, ..Settings::default() };
+        store.set(settings).unwrap();
+        assert_eq!(store.get().max_concurrent_runs, 3);
+        let loaded = SettingsStore::load(config.clone()).get();
+        assert_eq!((loaded.max_concurrent_runs, loaded.engine_path.as_deref()), (3, Some("/opt/engine")));
+
+        // Fields missing from an older file keep their defaults; a corrupt file is ignored
+        std::fs::write(config.join(SETTINGS_FILE), r#"{"maxConcurrentRuns": 2}"#).unwrap();
+        let loaded = SettingsStore::load(config.clone()).get();
+        assert_eq!((loaded.max_concurrent_runs, loaded.history_quota_mb), (2, DEFAULT_HISTORY_QUOTA_MB));
+        std::fs::write(config.join(SETTINGS_FILE), "{").unwrap();
+        assert_eq!(SettingsStore::load(config).get().max_concurrent_runs, 0);
+    }
+}
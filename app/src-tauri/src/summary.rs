@@ -0,0 +1,131 @@
+//! Run summary parsed from the engine's verbose (`-v`) stdout.
+//!
+//! `main.cpp` prints the network size before solving, then either the
+//! steady solver line `Converged in <n> iterations (max residual: <r> kg/s)`
+//! or the transient window and `Completed (<n> output steps)`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::options::SolverMethod;
+
+/// What the engine reported about a run. Fields stay `None` when the line
+/// was not printed: quiet runs, or lines belonging to the other kind of run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RunSummary {
+    pub node_count: Option<usize>,
+    pub link_count: Option<usize>,
+    /// Nodes whose pressure is solved for
+    pub unknown_count: Option<usize>,
+    pub species_count: Option<usize>,
+    pub source_count: Option<usize>,
+    /// Printed for steady solves only
+    pub method: Option<SolverMethod>,
+    pub converged: Option<bool>,
+    pub iterations: Option<u32>,
+    /// [kg/s]
+    pub max_residual: Option<f64>,
+    /// Transient window [s]
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    pub time_step: Option<f64>,
+    /// Transient output steps written
+    pub output_steps: Option<usize>,
+}
+
+impl RunSummary {
+    /// Parse the stdout kept in `EngineLogs`; unrecognized lines are ignored.
+    pub fn parse(stdout: &str) -> Self {
+        let mut summary = Self::default();
+        for line in stdout.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("Network:") {
+                // `<n> nodes, <m> links`
+                let (nodes, links) = rest.split_once(',').unwrap_or((rest, ""));
+                summary.node_count = leading_number(nodes, "nodes");
+                summary.link_count = leading_number(links, "links");
+            } else if let Some(rest) = line.strip_prefix("Unknown pressures:") {
+                summary.unknown_count = rest.trim().parse().ok();
+            } else if let Some(rest) = line.strip_prefix("Species:") {
+                summary.species_count = rest.trim().parse().ok();
+            } else if let Some(rest) = line.strip_prefix("Sources:") {
+                summary.source_count = rest.trim().parse().ok();
+            } else if let Some(rest) = line.strip_prefix("Solving steady-state with") {
+                summary.method = match rest.trim().strip_suffix("method...") {
+                    Some("Trust Region ") => Some(SolverMethod::TrustRegion),
+                    Some("Sub-Relaxation ") => Some(SolverMethod::SubRelaxation),
+                    _ => None,
+                };
+            } else if let Some(rest) = line.strip_prefix("Converged in") {
+                summary.parse_solve(rest, true);
+            } else if let Some(rest) = line.strip_prefix("FAILED to converge in") {
+                summary.parse_solve(rest, false);
+            } else if let Some(rest) = line.strip_prefix("Running transient simulation:") {
+                summary.parse_window(rest);
+            } else if let Some(rest) = line.strip_prefix("Completed (").or_else(|| line.strip_prefix("Incomplete (")) {
+                summary.output_steps = leading_number(rest, "output steps)");
+            }
+        }
+        summary
+    }
+
+    /// ` <n> iterations (max residual: <r> kg/s)`
+    fn parse_solve(&mut self, rest: &str, converged: bool) {
+        self.converged = Some(converged);
+        let (iterations, residual) = rest.split_once('(').unwrap_or((rest, ""));
+        self.iterations = leading_number(iterations, "iterations");
+        self.max_residual = residual
+            .trim()
+            .strip_prefix("max residual:")
+            .and_then(|r| r.trim().strip_suffix("kg/s)"))
+            .and_then(|r| r.trim().parse().ok());
+    }
+
+    /// ` <start>s to <end>s (dt=<dt>s)...`
+    fn parse_window(&mut self, rest: &str) {
+        let Some((start, rest)) = rest.trim().split_once("s to ") else { return };
+        self.start_time = start.parse().ok();
+        let Some((end, rest)) = rest.split_once("s (dt=") else { return };
+        self.end_time = end.parse().ok();
+        self.time_step = rest.split_once("s)").and_then(|(dt, _)| dt.parse().ok());
+    }
+}
+
+/// `<n> <unit>` → `n`
+fn leading_number<T: std::str::FromStr>(text: &str, unit: &str) -> Option<T> {
+    text.trim().strip_suffix(unit)?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_steady_output() {
+        let stdout = "Reading input: in.json\nNetwork: 3 nodes, 3 links\nUnknown pressures: 2\n\
+                      Solving steady-state with Sub-Relaxation method...\n\
+                      Converged in 42 iterations (max residual: 3.5e-07 kg/s)\nResults written to: out.json\n";
+        let expected = RunSummary {
+            node_count: Some(3),
+            link_count: Some(3),
+            unknown_count: Some(2),
+            method: Some(SolverMethod::SubRelaxation),
+            converged: Some(true),
+            iterations: Some(42),
+            max_residual: Some(3.5e-7),
+            ..RunSummary::default()
+        };
+        assert_eq!(RunSummary::parse(stdout), expected);
+    }
+
+    #[test]
+    fn parses_transient_output() {
+        let stdout = "Network: 3 nodes, 2 links\nUnknown pressures: 2\nSpecies: 1\nSources: 1\n\
+                      Running transient simulation: 0s to 3600s (dt=60s)...\n\
+                      Incomplete (12 output steps)\n";
+        let summary = RunSummary::parse(stdout);
+        assert_eq!((summary.species_count, summary.source_count, summary.method), (Some(1), Some(1), None));
+        assert_eq!((summary.start_time, summary.end_time, summary.time_step), (Some(0.0), Some(3600.0), Some(60.0)));
+        assert_eq!((summary.output_steps, summary.converged), (Some(12), None));
+        assert_eq!(RunSummary::parse(""), RunSummary::default(), "quiet runs print nothing");
+    }
+}
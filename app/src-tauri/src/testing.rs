@@ -0,0 +1,111 @@
+//! Fixtures shared by the unit tests of several modules.
+
+#[cfg(unix)]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::engine::RunOutcome;
+use crate::error::EngineError;
+use crate::history::HistoryStore;
+use crate::launch::{Launcher, RunRequest};
+use crate::limits::RunLimits;
+use crate::options::RunOptions;
+#[cfg(unix)]
+use crate::runner::ProcessRunner;
+use crate::runner::{EngineRunner, MockRunner};
+use crate::runs::JobManager;
+use crate::workdir::WorkDir;
+
+pub const CASES: [&str; 4] = ["case01_3room", "case02_co2_source", "case03_fan_duct", "case04_multizone"];
+
+/// A scratch directory removed at the end of the test.
+pub struct TestDir(pub PathBuf);
+
+impl TestDir {
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("airsim-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A launcher with its own queue, work directory and history under `dir`.
+pub fn launcher(dir: &TestDir) -> Launcher {
+    Launcher {
+        jobs: JobManager::new(0, Box::new(|_, _| {})),
+        workdir: WorkDir::create(dir.0.join("runs")).unwrap(),
+        history: HistoryStore::create(dir.0.join("history")).unwrap(),
+        on_progress: Arc::new(|_, _| {}),
+        on_segment: Arc::new(|_, _| {}),
+    }
+}
+
+pub fn case_input(case: &str) -> String {
+    std::fs::read_to_string(MockRunner::case_dir(case).join("input.json")).unwrap()
+}
+
+pub fn case_output(case: &str) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(MockRunner::case_dir(case).join("output.json")).unwrap()).unwrap()
+}
+
+/// A plain run of `input`: default options and limits, not recorded.
+pub fn request(input: String) -> RunRequest {
+    RunRequest {
+        input,
+        options: RunOptions::default(),
+        limits: RunLimits::default(),
+        fresh: false,
+        history_quota: None,
+        retry: None,
+        warm_start: false,
+        segments: None,
+    }
+}
+
+/// Launch `request` on `runner` and wait for its outcome.
+pub fn run(launcher: &Launcher, runner: impl EngineRunner + 'static, request: RunRequest) -> RunOutcome {
+    let run_id = launcher.launch(Arc::new(runner), request).unwrap();
+    launcher.jobs.wait(&run_id, Some(Duration::from_secs(30))).unwrap().expect("run did not finish")
+}
+
+/// The run completed with exactly the output of the validation case.
+pub fn assert_case_output(outcome: &RunOutcome, case: &str, expect_partial: bool) {
+    let RunOutcome::Completed { result, partial, .. } = outcome else { panic!("{}: {:?}", case, outcome) };
+    assert_eq!(*partial, expect_partial, "{}", case);
+    assert_eq!(serde_json::to_value(&**result).unwrap(), case_output(case), "{}", case);
+}
+
+pub fn assert_failed(outcome: &RunOutcome, matches: impl Fn(&EngineError) -> bool) {
+    let RunOutcome::Failed { error } = outcome else { panic!("expected a failure: {:?}", outcome) };
+    assert!(matches(error), "unexpected error: {:?}", error);
+}
+
+/// A `ProcessRunner` for a shell script standing in for `contam_engine`,
+/// which is called as `<script> -i <input> -o <output> ...`.
+#[cfg(unix)]
+pub fn fake_engine(dir: &Path, script: &str) -> ProcessRunner {
+    let path = dir.join(format!("fake_engine_{}.sh", uuid::Uuid::new_v4()));
+    std::fs::write(&path, script).unwrap();
+    // Run through `sh` rather than executing the script, which can fail
+    // with ETXTBSY while another test thread forks
+    ProcessRunner::new(MockRunner::engine_info(), move || {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg(&path);
+        cmd
+    })
+}
+
+#[cfg(unix)]
+pub fn copy_case_output(case: &str) -> String {
+    format!("cp '{}' \"$4\"\n", MockRunner::case_dir(case).join("output.json").display())
+}
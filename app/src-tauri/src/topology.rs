@@ -0,0 +1,684 @@
+//! Typed model of the engine's topology input, as read by `JsonReader`.
+//!
+//! Optional fields fall back to the engine's defaults; required ones are the
+//! keys `JsonReader` reads without a default. The JSON Schema used for
+//! pre-flight validation is generated from these types, and
+//! `schemas/topology.schema.json` is that schema checked in for other tools.
+//! A test fails when it drifts from the types; regenerate it with
+//! `AIRSIM_UPDATE_SCHEMA=1 cargo test --no-default-features schema_file`.
+
+use std::collections::BTreeMap;
+
+use schemars::gen::SchemaSettings;
+use schemars::schema::RootSchema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Building topology and airflow network definition (v2.0).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[schemars(title = "AirSim Studio Topology Input")]
+pub struct Topology {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambient: Option<Ambient>,
+    /// Named element definitions that links can reference by key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub flow_elements: BTreeMap<String, FlowElement>,
+    pub nodes: Vec<Node>,
+    pub links: Vec<Link>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub species: Vec<Species>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<Source>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<Schedule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub week_schedules: Vec<WeekSchedule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub day_types: Vec<DayType>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zone_temperature_schedules: Vec<ZoneTemperatureSchedule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub occupants: Vec<Occupant>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub controls: Option<Controls>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weather: Option<Weather>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ahs_systems: Vec<AhsSystem>,
+    /// Present for transient runs; steady-state otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transient: Option<TransientConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Ambient {
+    /// [K]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Gauge pressure, usually 0 [Pa]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<f64>,
+    /// [m/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind_speed: Option<f64>,
+    /// Degrees from north
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind_direction: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum NodeType {
+    #[default]
+    Normal,
+    Ambient,
+    Phantom,
+    Cfd,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Node {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, rename = "type")]
+    pub node_type: NodeType,
+    /// [K]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// [m]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevation: Option<f64>,
+    /// [m³]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f64>,
+    /// Gauge pressure, also the solver's initial guess [Pa]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<f64>,
+    /// Wind pressure coefficient of an ambient node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind_cp: Option<f64>,
+    /// Degrees from north
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wall_azimuth: Option<f64>,
+    /// Wind speed modifier for the local terrain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terrain_factor: Option<f64>,
+    /// Cp vs. wind angle, instead of `windCp`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wind_pressure_profile: Vec<WindPressurePoint>,
+    /// Initial concentration per species ID [kg/m³]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub initial_concentrations: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WindPressurePoint {
+    /// Degrees
+    pub angle: f64,
+    pub cp: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Link {
+    pub id: i32,
+    /// Node ID
+    pub from: i32,
+    /// Node ID
+    pub to: i32,
+    /// [m]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevation: Option<f64>,
+    /// A link without an element carries no flow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element: Option<LinkElement>,
+    /// Schedule scaling the element; -1 = none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum LinkElement {
+    /// Key into `flowElements`
+    Reference(String),
+    Inline(FlowElement),
+}
+
+/// Flow element, selected by `type`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum FlowElement {
+    /// Given as `C`/`n`, `leakageArea` or `orificeArea`
+    #[serde(rename_all = "camelCase")]
+    PowerLawOrifice {
+        #[serde(rename = "C", default, skip_serializing_if = "Option::is_none")]
+        c: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        n: Option<f64>,
+        /// ASHRAE effective leakage area [m²]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        leakage_area: Option<f64>,
+        /// Reference pressure difference for `leakageArea` [Pa]
+        #[serde(rename = "dPref", default, skip_serializing_if = "Option::is_none")]
+        d_pref: Option<f64>,
+        /// Equivalent orifice area [m²]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        orifice_area: Option<f64>,
+        /// Discharge coefficient for `orificeArea`
+        #[serde(rename = "Cd", default, skip_serializing_if = "Option::is_none")]
+        cd: Option<f64>,
+    },
+    /// Large bidirectional opening such as a door
+    #[serde(rename_all = "camelCase")]
+    TwoWayFlow {
+        #[serde(rename = "Cd")]
+        cd: f64,
+        /// [m²]
+        area: f64,
+        /// [m]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        height: Option<f64>,
+        /// [m]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        width: Option<f64>,
+    },
+    /// Given as a `coeffs` polynomial, or `maxFlow` and `shutoffPressure`
+    #[serde(rename_all = "camelCase")]
+    Fan {
+        /// Flow at ΔP=0 [m³/s]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_flow: Option<f64>,
+        /// [Pa]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        shutoff_pressure: Option<f64>,
+        /// Fan curve polynomial coefficients
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        coeffs: Option<Vec<f64>>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Duct {
+        /// [m]
+        length: f64,
+        /// Hydraulic diameter [m]
+        diameter: f64,
+        /// [m]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        roughness: Option<f64>,
+        /// Sum of minor loss coefficients
+        #[serde(rename = "sumK", default, skip_serializing_if = "Option::is_none")]
+        sum_k: Option<f64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Damper {
+        /// Flow coefficient when fully open
+        #[serde(rename = "Cmax")]
+        c_max: f64,
+        n: f64,
+        /// Opening fraction 0-1
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fraction: Option<f64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Filter {
+        #[serde(rename = "C")]
+        c: f64,
+        n: f64,
+        /// Removal efficiency 0-1
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        efficiency: Option<f64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    SelfRegulatingVent {
+        /// [m³/s]
+        target_flow: f64,
+        /// [Pa]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        p_min: Option<f64>,
+        /// [Pa]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        p_max: Option<f64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    CheckValve {
+        #[serde(rename = "C")]
+        c: f64,
+        n: f64,
+    },
+    /// Filter whose efficiency drops with the captured mass
+    #[serde(rename_all = "camelCase")]
+    SimpleGaseousFilter {
+        #[serde(rename = "C")]
+        c: f64,
+        n: f64,
+        /// Efficiency vs. loading; ignored with fewer than 2 points
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        loading_table: Vec<LoadingPoint>,
+        /// Efficiency below which the filter is spent
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        breakthrough_threshold: Option<f64>,
+    },
+    /// Ultraviolet germicidal irradiation section
+    #[serde(rename = "UVGIFilter", rename_all = "camelCase")]
+    UvgiFilter {
+        #[serde(rename = "C")]
+        c: f64,
+        n: f64,
+        /// Susceptibility [m²/J]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        k: Option<f64>,
+        /// [W/m²]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        irradiance: Option<f64>,
+        /// [m³]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        chamber_volume: Option<f64>,
+        /// Lamp output loss per hour
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        aging_rate: Option<f64>,
+        /// [h]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lamp_age_hours: Option<f64>,
+        /// Temperature correction polynomial
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        temp_coeffs: Option<Vec<f64>>,
+        /// Flow correction polynomial
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        flow_coeffs: Option<Vec<f64>>,
+    },
+    /// Drawn by the editor; carries no flow in the engine
+    SupplyDiffuser {},
+    /// Drawn by the editor; carries no flow in the engine
+    ReturnGrille {},
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LoadingPoint {
+    /// Captured mass [kg]
+    pub loading: f64,
+    /// 0-1
+    pub efficiency: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Species {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// [kg/mol]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub molar_mass: Option<f64>,
+    /// [1/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay_rate: Option<f64>,
+    /// [kg/m³]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outdoor_concentration: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_trace: Option<bool>,
+    /// [m²/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diffusion_coeff: Option<f64>,
+    /// Particle diameter [m]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_diameter: Option<f64>,
+    /// Particle density [kg/m³]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_density: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum SourceType {
+    #[default]
+    Constant,
+    ExponentialDecay,
+    PressureDriven,
+    CutoffConcentration,
+    Burst,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Source {
+    /// Node ID
+    pub zone_id: i32,
+    pub species_id: i32,
+    #[serde(default, rename = "type")]
+    pub source_type: SourceType,
+    /// [kg/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation_rate: Option<f64>,
+    /// [1/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removal_rate: Option<f64>,
+    /// -1 = none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_id: Option<i32>,
+    /// ExponentialDecay [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay_time_constant: Option<f64>,
+    /// ExponentialDecay [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<f64>,
+    /// ExponentialDecay
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<f64>,
+    /// PressureDriven [kg/(s·Pa)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure_coeff: Option<f64>,
+    /// CutoffConcentration [kg/m³]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cutoff_concentration: Option<f64>,
+    /// Burst [kg]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst_mass: Option<f64>,
+    /// Burst [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst_time: Option<f64>,
+    /// Burst [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst_duration: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Schedule {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub points: Vec<SchedulePoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SchedulePoint {
+    /// [s]
+    pub time: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekSchedule {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Day type ID for each weekday, Monday first
+    #[schemars(length(equal = 7))]
+    pub day_types: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DayType {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Schedule followed on days of this type
+    pub schedule_id: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneTemperatureSchedule {
+    pub node_id: i32,
+    /// Schedule whose values are temperatures [K]
+    pub schedule_id: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Occupant {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// [m³/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breathing_rate: Option<f64>,
+    /// Exhaled CO2 [kg/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub co2_emission_rate: Option<f64>,
+    /// Node ID, when there is no `schedule`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_id: Option<i32>,
+    /// Where the occupant is over time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<OccupantZoneAssignment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OccupantZoneAssignment {
+    /// [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<f64>,
+    /// [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<f64>,
+    /// Node ID; -1 = outside the building
+    pub zone_id: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Controls {
+    #[serde(default)]
+    pub sensors: Vec<Sensor>,
+    #[serde(default)]
+    pub controllers: Vec<Controller>,
+    #[serde(default)]
+    pub actuators: Vec<Actuator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum SensorType {
+    Concentration,
+    Pressure,
+    Temperature,
+    MassFlow,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Sensor {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub sensor_type: SensorType,
+    pub target_id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub species_idx: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Controller {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub sensor_id: i32,
+    pub actuator_id: i32,
+    pub setpoint: f64,
+    #[serde(rename = "Kp", default, skip_serializing_if = "Option::is_none")]
+    pub kp: Option<f64>,
+    #[serde(rename = "Ki", default, skip_serializing_if = "Option::is_none")]
+    pub ki: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadband: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ActuatorType {
+    DamperFraction,
+    FanSpeed,
+    FilterBypass,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Actuator {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub actuator_type: ActuatorType,
+    pub link_idx: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Weather {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Source `.wth` file, for reference only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    /// Hourly records
+    #[serde(default)]
+    pub records: Vec<WeatherRecord>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WeatherRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub month: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hour: Option<i32>,
+    /// [K]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// [m/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind_speed: Option<f64>,
+    /// Degrees from north
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind_direction: Option<f64>,
+    /// Absolute pressure [Pa]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<f64>,
+    /// Relative humidity 0-1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub humidity: Option<f64>,
+}
+
+/// Simple air handling system.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AhsSystem {
+    pub id: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// [m³/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supply_flow: Option<f64>,
+    /// [m³/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_flow: Option<f64>,
+    /// [m³/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outdoor_air_flow: Option<f64>,
+    /// [m³/s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exhaust_flow: Option<f64>,
+    /// [K]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supply_temperature: Option<f64>,
+    /// -1 = constant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outdoor_air_schedule_id: Option<i32>,
+    /// -1 = constant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supply_flow_schedule_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supply_zones: Vec<AhsZone>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub return_zones: Vec<AhsZone>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AhsZone {
+    /// Node ID
+    pub zone_id: i32,
+    /// Share of the system flow 0-1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fraction: Option<f64>,
+}
+
+/// Airflow solver used at every transient step.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum AirflowMethod {
+    #[default]
+    TrustRegion,
+    SubRelaxation,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransientConfig {
+    /// [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<f64>,
+    /// [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<f64>,
+    /// [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_step: Option<f64>,
+    /// [s]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_interval: Option<f64>,
+    /// Overridden by the engine's `-m`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airflow_method: Option<AirflowMethod>,
+    /// Airflow iterations per step before giving up; 100 if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub airflow_max_iterations: Option<u32>,
+    /// Sub-relaxation factor of the `subRelaxation` method; 0.75 if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0.01, max = 1.0))]
+    pub airflow_relax_factor: Option<f64>,
+}
+
+/// Draft-07 JSON Schema for `Topology`. Optional fields are plain optional
+/// properties: `JsonReader` rejects explicit nulls.
+pub fn schema() -> RootSchema {
+    SchemaSettings::draft07()
+        .with(|s| s.option_add_null_type = false)
+        .into_generator()
+        .into_root_schema_for::<Topology>()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn schema_file_matches_the_types() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../schemas/topology.schema.json");
+        // Four-space indents, as the file has always had
+        let mut generated = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+        schema().serialize(&mut serde_json::Serializer::with_formatter(&mut generated, formatter)).unwrap();
+        let generated = String::from_utf8(generated).unwrap() + "\n";
+        if std::env::var_os("AIRSIM_UPDATE_SCHEMA").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+        let checked_in = std::fs::read_to_string(&path).unwrap().replace("\r\n", "\n");
+        assert!(checked_in == generated, "{} is out of date; see the module docs to regenerate it", path.display());
+    }
+}
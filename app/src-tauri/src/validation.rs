@@ -0,0 +1,107 @@
+//! Pre-flight validation of run input against the schema generated from
+//! `topology::Topology`, so a malformed model is reported field by field
+//! instead of surfacing as an engine exception.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::ValidationError;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::topology;
+
+/// Violations reported per input; the rest are usually follow-on errors.
+const MAX_VIOLATIONS: usize = 100;
+
+/// One schema violation, located by a JSON pointer into the input.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaViolation {
+    /// e.g. `/links/3/element/type`; empty for the document itself
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
+fn validator() -> &'static jsonschema::Validator {
+    static VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+    VALIDATOR.get_or_init(|| {
+        let schema = serde_json::to_value(topology::schema()).expect("topology schema serializes");
+        jsonschema::validator_for(&schema).expect("topology schema is a valid JSON Schema")
+    })
+}
+
+/// Check `input` against the topology schema. Offending values are left out
+/// of the messages, since a single one can be an entire weather file.
+pub fn validate_input(input: &str) -> Result<(), Vec<SchemaViolation>> {
+    let value: Value = serde_json::from_str(input).map_err(|e| {
+        vec![SchemaViolation { pointer: String::new(), message: format!("Invalid JSON: {}", e) }]
+    })?;
+    let mut violations = Vec::new();
+    for error in validator().iter_errors(&value) {
+        collect(&error, &mut violations);
+        if violations.len() >= MAX_VIOLATIONS {
+            break;
+        }
+    }
+    violations.truncate(MAX_VIOLATIONS);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Flow elements and link element references are `oneOf`/`anyOf` unions
+/// selected by `type`, and "not valid under any of the schemas" says nothing
+/// useful. Report the errors of the one variant the value selects, or what is
+/// wrong with its `type` when it selects none.
+fn collect(error: &ValidationError, out: &mut Vec<SchemaViolation>) {
+    let pointer = error.instance_path().to_string();
+    let branches = match error.kind() {
+        ValidationErrorKind::OneOfNotValid { context } | ValidationErrorKind::AnyOf { context } => context,
+        _ => {
+            out.push(SchemaViolation { pointer, message: error.masked().to_string() });
+            return;
+        }
+    };
+    let mut selected = branches.iter().filter(|branch| !branch.iter().any(|e| rejects_variant(e, &pointer)));
+    match (selected.next(), selected.next()) {
+        (Some(branch), None) => branch.iter().for_each(|e| collect(e, out)),
+        (None, _) => {
+            let (pointer, message) = match error.instance().get("type") {
+                Some(_) => (format!("{}/type", pointer), "unknown type"),
+                None if error.instance().is_object() => (pointer, "\"type\" is a required property"),
+                None => (pointer, "has the wrong JSON type"),
+            };
+            out.push(SchemaViolation { pointer, message: message.to_string() });
+        }
+        _ => out.push(SchemaViolation { pointer, message: error.masked().to_string() }),
+    }
+}
+
+/// Whether `error` shows the value at `pointer` is not this variant at all:
+/// a different `type` tag or a different JSON type.
+fn rejects_variant(error: &ValidationError, pointer: &str) -> bool {
+    let path = error.instance_path().to_string();
+    if path.strip_prefix(pointer) == Some("/type") {
+        return true;
+    }
+    path == pointer
+        && match error.kind() {
+            ValidationErrorKind::Type { .. } => true,
+            ValidationErrorKind::Required { property } => property.as_str() == Some("type"),
+            _ => false,
+        }
+}
@@ -0,0 +1,81 @@
+//! Warm starts: seeding a run with the node pressures of an earlier run of
+//! the same model, so the solver begins near the answer.
+//!
+//! `JsonReader` reads a node's `pressure` as its starting gauge pressure,
+//! except on ambient nodes where it is a boundary condition; those are left
+//! alone.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+/// `input` with the `pressure` of every non-ambient node that has an entry
+/// in `pressures` replaced, and the number of nodes changed. `None` if no
+/// node matched or the input is not a model.
+pub fn inject_pressures(input: &str, pressures: &BTreeMap<i32, f64>) -> Option<(String, usize)> {
+    let mut model: Value = serde_json::from_str(input).ok()?;
+    let injected = set_pressures(&mut model, pressures);
+    if injected == 0 {
+        return None;
+    }
+    Some((serde_json::to_string(&model).ok()?, injected))
+}
+
+/// `inject_pressures` on a parsed model; returns the number of nodes changed.
+pub fn set_pressures(model: &mut Value, pressures: &BTreeMap<i32, f64>) -> usize {
+    let Some(nodes) = model.get_mut("nodes").and_then(Value::as_array_mut) else { return 0 };
+    let mut injected = 0;
+    for node in nodes {
+        if node["type"].as_str() == Some("ambient") {
+            continue;
+        }
+        let id = node["id"].as_i64().and_then(|id| i32::try_from(id).ok());
+        let Some(&pressure) = id.and_then(|id| pressures.get(&id)) else { continue };
+        let Some(node) = node.as_object_mut() else { continue };
+        node.insert("pressure".to_string(), pressure.into());
+        injected += 1;
+    }
+    injected
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::launch::RunRequest;
+    use crate::runner::MockRunner;
+    use crate::testing::{case_input, launcher, request, TestDir};
+
+    #[test]
+    fn warm_start_uses_pressures_of_the_latest_run() {
+        let dir = TestDir::new();
+        let launcher = launcher(&dir);
+        let input = case_input("case01_3room");
+        let warm_run = || {
+            let request =
+                RunRequest { warm_start: true, fresh: true, history_quota: Some(u64::MAX), ..request(input.clone()) };
+            let run_id = launcher.launch(Arc::new(MockRunner::case("case01_3room")), request).unwrap();
+            launcher.jobs.wait(&run_id, Some(Duration::from_secs(30))).unwrap().unwrap();
+            launcher.history.load(&run_id).unwrap()
+        };
+
+        // Nothing to start from yet
+        let first = warm_run();
+        assert_eq!(first.entry.warm_start_run_id, None);
+        assert!(first.entry.model_key.is_some());
+
+        let second = warm_run();
+        assert_eq!(second.entry.warm_start_run_id.as_deref(), Some(first.entry.run_id.as_str()));
+        assert_eq!(second.entry.model_key, first.entry.model_key);
+        assert_eq!(second.input, input, "the history keeps the input as submitted");
+
+        let (_, pressures) = launcher.history.latest_pressures(first.entry.model_key.as_deref().unwrap()).unwrap();
+        let (seeded, count) = inject_pressures(&input, &pressures).unwrap();
+        assert_eq!(count, 2, "the ambient node keeps its boundary pressure");
+        let seeded: serde_json::Value = serde_json::from_str(&seeded).unwrap();
+        assert_eq!(seeded["nodes"][0].get("pressure"), None);
+        assert_eq!(seeded["nodes"][1]["pressure"].as_f64(), pressures.get(&1).copied());
+    }
+}
@@ -0,0 +1,202 @@
+//! Private working directory for engine temp files.
+//!
+//! Models contain client building data, so run inputs and outputs live in a
+//! per-app directory readable only by the current user rather than the shared
+//! system temp dir, and are removed as soon as the run is over. Results too
+//! large for memory spill to files here for as long as they are loaded.
+//! Finished segments of a segmented run are kept under `checkpoints/` until
+//! the run completes, so an interrupted run can resume.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+#[cfg(any(feature = "gui", test))]
+use std::time::{Duration, SystemTime};
+
+const INPUT_PREFIX: &str = "contam_input_";
+const OUTPUT_PREFIX: &str = "contam_output_";
+const COLUMNS_PREFIX: &str = "contam_columns_";
+const CHECKPOINTS_DIR: &str = "checkpoints";
+
+#[derive(Debug, Clone)]
+pub struct WorkDir {
+    path: PathBuf,
+}
+
+impl WorkDir {
+    /// Create `path` if needed and restrict it to the current user.
+    pub fn create(path: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&path)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700))?;
+        }
+        Ok(Self { path })
+    }
+
+    pub fn files(&self, run_id: &str) -> RunFiles {
+        RunFiles {
+            dir: self.path.clone(),
+            input: self.path.join(format!("{}{}.json", INPUT_PREFIX, run_id)),
+            output: self.path.join(format!("{}{}.json", OUTPUT_PREFIX, run_id)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Directory for the finished segments of runs with this cache key.
+    pub fn checkpoints(&self, key: &str) -> PathBuf {
+        self.path.join(CHECKPOINTS_DIR).join(key)
+    }
+
+    /// Remove run files and checkpoints left behind by a crash that are older
+    /// than `max_age`. Returns the number of entries removed.
+    #[cfg(any(feature = "gui", test))]
+    pub fn sweep(&self, max_age: Duration) -> usize {
+        let Ok(entries) = std::fs::read_dir(&self.path) else { return 0 };
+        let now = SystemTime::now();
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if ![INPUT_PREFIX, OUTPUT_PREFIX, COLUMNS_PREFIX].iter().any(|prefix| name.starts_with(prefix)) {
+                continue;
+            }
+            let stale = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(|modified| now.duration_since(modified).unwrap_or_default() >= max_age)
+                .unwrap_or(false);
+            if stale && std::fs::remove_file(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+        // Checkpoints of segmented runs that were never resumed
+        let Ok(entries) = std::fs::read_dir(self.path.join(CHECKPOINTS_DIR)) else { return removed };
+        for entry in entries.flatten() {
+            let stale = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(|modified| now.duration_since(modified).unwrap_or_default() >= max_age)
+                .unwrap_or(false);
+            if stale && std::fs::remove_dir_all(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+/// Temp input/output files belonging to a single run. Both are removed when
+/// this is dropped, so every exit path (including a panic) cleans up.
+pub struct RunFiles {
+    /// The work directory, for results that spill to disk
+    pub dir: PathBuf,
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+impl RunFiles {
+    /// Write the run's input with owner-only permissions.
+    pub fn write_input(&self, input: &str) -> std::io::Result<()> {
+        create_private(&self.input)?.write_all(input.as_bytes())
+    }
+}
+
+impl Drop for RunFiles {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.input);
+        let _ = std::fs::remove_file(&self.output);
+    }
+}
+
+/// A new, unique file name for a spilled result column in `dir`.
+pub fn spill_file(dir: &Path) -> PathBuf {
+    dir.join(format!("{}{}.bin", COLUMNS_PREFIX, uuid::Uuid::new_v4()))
+}
+
+pub fn create_private(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    // Readable too, so spilled columns can be mapped
+    options.read(true).write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestDir;
+
+    const MAX_AGE: Duration = Duration::from_secs(3600);
+
+    /// Backdate `path` to two hours ago.
+    #[cfg(unix)]
+    fn make_stale(path: &Path) {
+        File::open(path).unwrap().set_modified(SystemTime::now() - 2 * MAX_AGE).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sweep_removes_only_stale_run_files_and_checkpoints() {
+        let dir = TestDir::new();
+        let work = WorkDir::create(dir.0.join("work")).unwrap();
+        let stale_run = work.files("stale");
+        let fresh_run = work.files("fresh");
+        let stale_column = spill_file(work.path());
+        for path in [&stale_run.input, &stale_run.output, &fresh_run.input, &stale_column] {
+            create_private(path).unwrap();
+        }
+        let unrelated = work.path().join("settings.json");
+        std::fs::write(&unrelated, "{}").unwrap();
+        let stale_checkpoint = work.checkpoints("stale-key");
+        let fresh_checkpoint = work.checkpoints("fresh-key");
+        for checkpoint in [&stale_checkpoint, &fresh_checkpoint] {
+            std::fs::create_dir_all(checkpoint).unwrap();
+            std::fs::write(checkpoint.join("segment0.json"), "{}").unwrap();
+        }
+        for path in [&stale_run.input, &stale_run.output, &stale_column, &unrelated, &stale_checkpoint] {
+            make_stale(path);
+        }
+
+        assert_eq!(work.sweep(MAX_AGE), 4);
+        assert!(!stale_run.input.exists() && !stale_run.output.exists() && !stale_column.exists());
+        assert!(!stale_checkpoint.exists());
+        assert!(fresh_run.input.exists(), "a run in progress keeps its files");
+        assert!(fresh_checkpoint.join("segment0.json").exists());
+        assert!(unrelated.exists(), "only run files are swept");
+        assert_eq!(work.sweep(MAX_AGE), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_files_are_private_and_removed_on_drop() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        let dir = TestDir::new();
+        let path = dir.0.join("work");
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let work = WorkDir::create(path).unwrap();
+        assert_eq!(mode(work.path()), 0o700, "an existing directory is restricted too");
+
+        let files = work.files("run1");
+        files.write_input("{}").unwrap();
+        create_private(&files.output).unwrap();
+        assert_eq!(mode(&files.input), 0o600);
+        assert_eq!(mode(&files.output), 0o600);
+        assert_eq!(std::fs::read_to_string(&files.input).unwrap(), "{}");
+
+        let (input, output) = (files.input.clone(), files.output.clone());
+        drop(files);
+        assert!(!input.exists() && !output.exists());
+    }
+}
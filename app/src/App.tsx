@@ -16,6 +16,7 @@ import { useCanvasStore } from './store/useCanvasStore';
 import { useState, useCallback } from 'react';
 import { ChevronDown, ChevronUp, PanelRightOpen, PanelRightClose, Layers, GitBranch, Loader2 } from 'lucide-react';
 import { toast } from './hooks/use-toast';
+import { useTransientStep } from './hooks/useTransientStep';
 import { Tabs, TabsList, TabsTrigger, TabsContent } from './components/ui/tabs';
 
 function BottomPanel() {
@@ -98,6 +99,7 @@ function App() {
   const setSidebarOpen = useCanvasStore(s => s.setSidebarOpen);
   const [showWelcome, setShowWelcome] = useState(true);
   const [activeView, setActiveView] = useState<'canvas' | 'control'>('canvas');
+  useTransientStep();
 
   const handleDragOver = useCallback((e: React.DragEvent) => {
     e.preventDefault();
@@ -302,12 +302,10 @@ export default function Canvas2D() {
       const appState = useAppStore.getState();
       const result = appState.result;
       const transientResult = appState.transientResult;
-      const currentTransientStep = state.currentTransientStep;
 
       // Transient results overlay (prefer transient if available)
-      if (transientResult && transientResult.timeSeries.length > 0) {
-        const stepIdx = Math.min(currentTransientStep, transientResult.timeSeries.length - 1);
-        const step = transientResult.timeSeries[stepIdx];
+      if (transientResult) {
+        const step = appState.transientStep;
         if (step) {
           // Build edge flows from transient massFlows
           const edgeFlows: EdgeFlowResult[] = [];
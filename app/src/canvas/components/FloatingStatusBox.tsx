@@ -1,8 +1,8 @@
-import { useState, useEffect, useMemo } from 'react';
+import { useState, useEffect } from 'react';
 import { useCanvasStore } from '../../store/useCanvasStore';
 import { useAppStore } from '../../store/useAppStore';
 import { faceArea } from '../../model/geometry';
-import type { NodeResult, TransientTimeStep } from '../../types';
+import type { NodeResult } from '../../types';
 
 // ── Helpers ──
 
@@ -41,7 +41,6 @@ export function FloatingStatusBox() {
   const hoveredFaceId = useCanvasStore(s => s.hoveredFaceId);
   const hoveredEdgeId = useCanvasStore(s => s.hoveredEdgeId);
   const appMode = useCanvasStore(s => s.appMode);
-  const currentTransientStep = useCanvasStore(s => s.currentTransientStep);
   const scaleFactor = useCanvasStore(s => s.scaleFactor);
   const story = useCanvasStore(s => s.getActiveStory());
 
@@ -60,12 +59,8 @@ export function FloatingStatusBox() {
     return () => window.removeEventListener('mousemove', handleMouseMove);
   }, []);
 
-  // Resolve active transient time step
-  const transientStep: TransientTimeStep | null = useMemo(() => {
-    if (!transientResult || transientResult.timeSeries.length === 0) return null;
-    const idx = Math.min(currentTransientStep, transientResult.timeSeries.length - 1);
-    return transientResult.timeSeries[idx] ?? null;
-  }, [transientResult, currentTransientStep]);
+  // Active transient time step, fetched by `useTransientStep`
+  const transientStep = useAppStore(s => s.transientStep);
 
   const isResults = appMode === 'results';
 
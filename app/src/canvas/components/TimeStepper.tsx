@@ -26,9 +26,9 @@ export function TimeStepper() {
   // Early return AFTER all hooks
   if (appMode !== 'results' || !transientResult) return null;
 
-  const totalSteps = transientResult.timeSeries.length;
-  const currentTime = transientResult.timeSeries[currentStep]?.time ?? 0;
-  const endTime = transientResult.timeSeries[totalSteps - 1]?.time ?? 0;
+  const totalSteps = transientResult.times.length;
+  const currentTime = transientResult.times[currentStep] ?? 0;
+  const endTime = transientResult.times[totalSteps - 1] ?? 0;
 
   // Playback logic
   useEffect(() => {
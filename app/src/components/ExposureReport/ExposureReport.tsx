@@ -1,11 +1,54 @@
+import { useEffect, useState } from 'react';
 import { useAppStore } from '../../store/useAppStore';
 import { ShieldAlert, Download } from 'lucide-react';
 import { toast } from '../../hooks/use-toast';
+import { sliceResult } from '../../utils/engine';
+import type { TransientResult } from '../../types';
+
+/** Concentrations of the zones occupants spend time in, at every output step */
+interface ZoneSlice {
+  result: TransientResult;
+  zoneIds: number[];
+  rows: Float64Array;
+}
 
 export default function ExposureReport() {
   const { transientResult, occupants, nodes } = useAppStore();
+  const [slice, setSlice] = useState<ZoneSlice | null>(null);
+
+  const times = transientResult?.times ?? [];
+  const dt = times.length > 1 ? times[1] - times[0] : 60;
+  const totalTime = times.length > 1 ? times[times.length - 1] - times[0] : 0;
+
+  // If no occupants defined, compute for all non-ambient zones (default occupant)
+  const effectiveOccupants = occupants.length > 0
+    ? occupants
+    : nodes.filter(n => n.type === 'normal').map((n, i) => ({
+        id: i,
+        name: `${n.name} (默认)`,
+        breathingRate: 1.2e-4,
+        co2EmissionRate: 0,
+        schedule: [{ startTime: 0, endTime: totalTime + dt, zoneId: n.id }],
+      }));
+
+  // Doses are integrated over every step, so the zones are fetched at full resolution, but only those
+  const zoneKey = [...new Set(effectiveOccupants.flatMap(occ => occ.schedule.map(a => a.zoneId)))]
+    .filter(id => transientResult?.nodes.some(n => n.id === id))
+    .sort((a, b) => a - b)
+    .join(',');
+  useEffect(() => {
+    if (!transientResult) return;
+    let stale = false;
+    const zoneIds = zoneKey ? zoneKey.split(',').map(Number) : [];
+    sliceResult(transientResult, { nodeIds: zoneIds, speciesIds: transientResult.species.map(s => s.id) })
+      .then((rows) => { if (!stale) setSlice({ result: transientResult, zoneIds, rows }); })
+      .catch((e: unknown) => {
+        if (!stale) toast({ title: '读取结果失败', description: e instanceof Error ? e.message : String(e), variant: 'destructive' });
+      });
+    return () => { stale = true; };
+  }, [transientResult, zoneKey]);
 
-  if (!transientResult || !transientResult.timeSeries || transientResult.timeSeries.length < 2) {
+  if (!transientResult || times.length < 2) {
     return (
       <div className="p-4 text-sm text-muted-foreground">
         <p>暴露报告需要瞬态仿真结果。请先运行瞬态仿真。</p>
@@ -13,13 +56,20 @@ export default function ExposureReport() {
     );
   }
 
-  const { timeSeries, species, nodes: resultNodes } = transientResult;
-  const dt = timeSeries.length > 1 ? timeSeries[1].time - timeSeries[0].time : 60;
-  const totalTime = timeSeries[timeSeries.length - 1].time - timeSeries[0].time;
+  const { species } = transientResult;
+  if (slice?.result !== transientResult) {
+    return (
+      <div className="p-4 text-sm text-muted-foreground">
+        <p>正在读取结果…</p>
+      </div>
+    );
+  }
 
-  // Build node index map: nodeId → index in timeSeries arrays
-  const nodeIdxMap = new Map<number, number>();
-  resultNodes.forEach((nd, idx) => { nodeIdxMap.set(nd.id, idx); });
+  // Rows are `[time, pressures.., concentrations..]` for the fetched zones
+  const { zoneIds, rows } = slice;
+  const zoneIdxMap = new Map(zoneIds.map((id, idx) => [id, idx]));
+  const width = 1 + zoneIds.length + zoneIds.length * species.length;
+  const stepCount = Math.floor(rows.length / width);
 
   // For each occupant, compute exposure per species
   interface ExposureEntry {
@@ -32,17 +82,6 @@ export default function ExposureReport() {
 
   const entries: ExposureEntry[] = [];
 
-  // If no occupants defined, compute for all non-ambient zones (default occupant)
-  const effectiveOccupants = occupants.length > 0
-    ? occupants
-    : nodes.filter(n => n.type === 'normal').map((n, i) => ({
-        id: i,
-        name: `${n.name} (默认)`,
-        breathingRate: 1.2e-4,
-        co2EmissionRate: 0,
-        schedule: [{ startTime: 0, endTime: totalTime + dt, zoneId: n.id }],
-      }));
-
   effectiveOccupants.forEach((occ) => {
     species.forEach((sp, spIdx) => {
       let cumulativeDose = 0;
@@ -50,22 +89,24 @@ export default function ExposureReport() {
       let totalExposure = 0;
       let exposureTime = 0;
 
-      timeSeries.forEach((ts) => {
+      for (let step = 0; step < stepCount; step++) {
+        const time = rows[step * width];
         // Find which zone the occupant is in at this time
         const assignment = occ.schedule.find(
-          (a) => ts.time >= a.startTime && ts.time < a.endTime
+          (a) => time >= a.startTime && time < a.endTime
         );
-        if (!assignment || assignment.zoneId < 0) return; // outside building
+        if (!assignment || assignment.zoneId < 0) continue; // outside building
 
-        const nodeIdx = nodeIdxMap.get(assignment.zoneId);
-        if (nodeIdx === undefined) return;
+        const zoneIdx = zoneIdxMap.get(assignment.zoneId);
+        if (zoneIdx === undefined) continue;
 
-        const conc = ts.concentrations?.[nodeIdx]?.[spIdx] ?? 0;
+        const raw = rows[step * width + 1 + zoneIds.length + zoneIdx * species.length + spIdx];
+        const conc = Number.isFinite(raw) ? raw : 0;
         cumulativeDose += conc * occ.breathingRate * dt;
         peakConc = Math.max(peakConc, conc);
         totalExposure += conc * dt;
         exposureTime += dt;
-      });
+      }
 
       const twa = exposureTime > 0 ? totalExposure / exposureTime : 0;
 
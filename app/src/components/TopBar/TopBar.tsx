@@ -1,18 +1,26 @@
 import { useAppStore } from '../../store/useAppStore';
 import { useCanvasStore } from '../../store/useCanvasStore';
-import { Play, Save, FolderOpen, Undo2, Redo2, Trash2, Moon, Sun, FileDown } from 'lucide-react';
+import { Play, Square, Save, FolderOpen, Undo2, Redo2, Trash2, Moon, Sun, FileDown, Zap, RefreshCw } from 'lucide-react';
 import { useState, useCallback, useEffect, useRef } from 'react';
 import { Button } from '../ui/button';
 import { Tooltip, TooltipContent, TooltipTrigger } from '../ui/tooltip';
 import { AlertDialog, AlertDialogAction, AlertDialogCancel, AlertDialogContent, AlertDialogDescription, AlertDialogFooter, AlertDialogHeader, AlertDialogTitle, AlertDialogTrigger } from '../ui/alert-dialog';
 import { toast } from '../../hooks/use-toast';
-import { canvasToTopology, validateModel, validateTopology, steadyResultToCSV, transientResultToCSV } from '../../model/dataBridge';
+import { useLiveSolve } from '../../hooks/useLiveSolve';
+import { canvasToTopology, validateModel, validateTopology, steadyResultToCSV, exportTransientResult } from '../../model/dataBridge';
 import { saveFile, openFile, downloadFile } from '../../utils/fileOps';
+import { runEngine, cancelRun, engineInfo, loadResult, RunCancelledError, type RunProgress } from '../../utils/engine';
+
+/** Transient runs longer than this are run in segments of this length [s] */
+const SEGMENT_LENGTH = 24 * 3600;
 
 export default function TopBar() {
   const { isRunning, clearAll, setResult, setIsRunning, setError, loadFromJson, species, setTransientResult, result, transientResult } = useAppStore();
   const setAppMode = useCanvasStore(s => s.setAppMode);
   const isTransient = species.length > 0;
+  // Live mode re-solves steady models in the backend as they are edited
+  const [live, setLive] = useState(false);
+  useLiveSolve(live && !isTransient);
   const [isDark, setIsDark] = useState(() => {
     // Persist dark mode preference
     const saved = localStorage.getItem('contam-dark-mode');
@@ -25,11 +33,16 @@ export default function TopBar() {
     }
     return document.documentElement.classList.contains('dark');
   });
+  // Re-solving with the other solver doubles the run time of a model that does not converge,
+  // so it is only done when asked for
+  const [autoRetry, setAutoRetry] = useState(() => localStorage.getItem('contam-auto-retry') === 'true');
   const hasResults = result !== null || transientResult !== null;
 
   // L-28: Elapsed time counter during simulation
   const [elapsed, setElapsed] = useState(0);
   const timerRef = useRef<ReturnType<typeof setInterval> | null>(null);
+  const runIdRef = useRef<string | null>(null);
+  const [progress, setProgress] = useState<RunProgress | null>(null);
   useEffect(() => {
     if (isRunning) {
       setElapsed(0);
@@ -48,17 +61,26 @@ export default function TopBar() {
     localStorage.setItem('contam-dark-mode', String(newDark));
   }, [isDark]);
 
+  const toggleAutoRetry = useCallback(() => {
+    setAutoRetry(!autoRetry);
+    localStorage.setItem('contam-auto-retry', String(!autoRetry));
+  }, [autoRetry]);
+
   const handleExportCSV = useCallback(async () => {
     if (transientResult) {
-      const csv = transientResultToCSV();
-      if (csv) await downloadFile(csv, 'transient_results.csv');
+      try {
+        await exportTransientResult();
+      } catch (e: unknown) {
+        toast({ title: '导出失败', description: e instanceof Error ? e.message : String(e), variant: 'destructive' });
+      }
     } else if (result) {
       const csv = steadyResultToCSV();
       if (csv) await downloadFile(csv, 'steady_results.csv');
     }
   }, [result, transientResult]);
 
-  const handleRun = async () => {
+  /** `fresh` (Shift+click) bypasses results cached from identical earlier runs */
+  const handleRun = async (fresh = false) => {
     // Validate model before running
     const { errors, warnings } = validateModel();
     if (errors.length > 0) {
@@ -87,17 +109,56 @@ export default function TopBar() {
       }
 
       if (window.__TAURI_INTERNALS__) {
-        const { invoke } = await import('@tauri-apps/api/core');
-        // L-27: 60s timeout for engine execution
-        const timeout = new Promise<never>((_, reject) => setTimeout(() => reject(new Error('仿真超时（60秒），请检查模型复杂度或引擎状态')), 60000));
-        const resultJson = await Promise.race([invoke<string>('run_engine', { input: JSON.stringify(topology) }), timeout]);
-        const parsed = JSON.parse(resultJson);
-        if (parsed.timeSeries) {
-          setTransientResult(parsed);
+        const info = await engineInfo();
+        if (!info.found) {
+          toast({ title: '未找到仿真引擎', description: `${info.path}：${info.error ?? ''}`, variant: 'destructive' });
+          return;
+        }
+        if (!info.compatible) {
+          toast({ title: '引擎版本可能不兼容', description: info.error ?? '' });
+        }
+        // Multi-day runs are checkpointed day by day: a cancelled run keeps the days already
+        // simulated, and running the same model again resumes after them
+        const span = topology.transient ? topology.transient.endTime - topology.transient.startTime : 0;
+        // Timeout and memory limits are enforced by the backend, which kills the engine process
+        const run = await runEngine(JSON.stringify(topology), {
+          fresh,
+          retry: autoRetry ? {} : undefined,
+          segments: span > SEGMENT_LENGTH ? { length: SEGMENT_LENGTH } : undefined,
+          onStarted: (id) => { runIdRef.current = id; },
+          onProgress: setProgress,
+        });
+        const result = await loadResult(run.runId);
+        if ('times' in result) {
+          setTransientResult(result);
+        } else {
+          setResult(result);
+        }
+        const transient = 'times' in result ? result : null;
+        if (run.partial) {
+          toast({
+            title: transient ? '瞬态仿真未完成' : '求解未收敛',
+            description: transient
+              ? `已显示前 ${transient.totalSteps} 步结果`
+              : `已显示最后一次迭代结果${run.maxResidual != null ? `（最大残差 ${run.maxResidual.toExponential(2)} kg/s）` : ''}，${run.attempts.length > 1 ? '两种求解器均未收敛，请检查模型中的气流路径参数' : autoRetry ? '可尝试改用亚松弛（SUR）求解器' : '可开启自动重试，未收敛时改用另一种求解器'}`,
+            variant: 'destructive',
+          });
         } else {
-          setResult(parsed);
+          const retried = run.attempts.find(a => a.selected && a !== run.attempts[0]);
+          const { iterations, maxResidual } = run.summary;
+          const stats = iterations != null
+            ? `，${iterations} 次迭代${maxResidual != null ? `，最大残差 ${maxResidual.toExponential(2)} kg/s` : ''}`
+            : '';
+          const solved = transient ? `瞬态仿真完成，${transient.totalSteps} 步` : `稳态收敛${stats}`;
+          const summary = retried
+            ? `${solved}（默认求解器未收敛，已自动改用${retried.method === 'sur' ? '亚松弛（SUR）' : '信赖域（TR）'}求解器）`
+            : solved;
+          toast({
+            title: '求解完成',
+            description: run.cachedRunId ? `${summary}（模型未变，已复用历史结果；Shift+点击可强制重新计算）` : summary,
+            variant: 'success',
+          });
         }
-        toast({ title: '求解完成', description: parsed.timeSeries ? `瞬态仿真完成，${parsed.totalSteps} 步` : '稳态收敛', variant: 'success' });
         setAppMode('results');
       } else {
         // C-05: Browser mode — warn user that results are mock data
@@ -113,9 +174,11 @@ export default function TopBar() {
             ),
           }));
           setTransientResult({
-            completed: true, totalSteps: numSteps,
+            runId: null, completed: true, totalSteps: numSteps,
             species: topology.species?.map((s) => ({ id: s.id, name: s.name, molarMass: s.molarMass })) ?? [],
             nodes: topology.nodes.map((n) => ({ id: n.id, name: n.name, type: n.type })),
+            linkCount: topology.links.length,
+            times: timeSeries.map((ts) => ts.time),
             timeSeries,
           });
         } else {
@@ -138,13 +201,23 @@ export default function TopBar() {
         setAppMode('results');
       }
     } catch (e: unknown) {
+      if (e instanceof RunCancelledError) {
+        toast({ title: '已取消', description: e.message });
+        return;
+      }
       setError(e instanceof Error ? e.message : String(e));
       toast({ title: '求解失败', description: e instanceof Error ? e.message : String(e), variant: 'destructive' });
     } finally {
+      runIdRef.current = null;
+      setProgress(null);
       setIsRunning(false);
     }
   };
 
+  const handleCancel = () => {
+    if (runIdRef.current) cancelRun(runIdRef.current).catch(() => {});
+  };
+
   const handleSave = async () => {
     const topology = canvasToTopology();
     const content = JSON.stringify(topology, null, 2);
@@ -192,19 +265,58 @@ export default function TopBar() {
       <div className="w-px h-7 bg-border mx-1.5" />
 
       {/* Run simulation */}
-      <Button onClick={handleRun} disabled={isRunning} size="sm"
+      <Button onClick={(e) => handleRun(e.shiftKey)} disabled={isRunning} size="sm"
         className="h-9 gap-1.5 px-4 text-sm font-semibold rounded-xl border-b-[3px] border-primary/50 active:border-b-0 active:translate-y-0.5 transition-all shadow-md"
       >
         <Play size={15} fill="currentColor" />
         {isRunning ? '计算中...' : (isTransient ? '瞬态仿真' : '稳态求解')}
       </Button>
 
+      {window.__TAURI_INTERNALS__ && !isTransient && (
+        <Tooltip delayDuration={200}>
+          <TooltipTrigger asChild>
+            <Button variant={live ? 'secondary' : 'ghost'} size="icon" className="h-9 w-9 rounded-xl ml-1"
+              onClick={() => { if (!live) setAppMode('results'); setLive(!live); }}
+            >
+              <Zap size={18} fill={live ? 'currentColor' : 'none'} />
+            </Button>
+          </TooltipTrigger>
+          <TooltipContent side="bottom" className="text-xs rounded-xl">{live ? '关闭实时求解' : '实时求解：编辑时自动更新压力与流量'}</TooltipContent>
+        </Tooltip>
+      )}
+
+      {window.__TAURI_INTERNALS__ && (
+        <Tooltip delayDuration={200}>
+          <TooltipTrigger asChild>
+            <Button variant={autoRetry ? 'secondary' : 'ghost'} size="icon" className="h-9 w-9 rounded-xl" onClick={toggleAutoRetry}>
+              <RefreshCw size={18} />
+            </Button>
+          </TooltipTrigger>
+          <TooltipContent side="bottom" className="text-xs rounded-xl">{autoRetry ? '关闭自动重试' : '自动重试：未收敛时改用另一种求解器重新求解'}</TooltipContent>
+        </Tooltip>
+      )}
+
       {isRunning && (
         <div className="flex items-center gap-1.5 ml-1.5">
           <div className="w-16 h-1.5 bg-muted rounded-full overflow-hidden">
-            <div className="h-full bg-primary rounded-full animate-pulse" style={{ width: '100%' }} />
+            {progress
+              ? <div className="h-full bg-primary rounded-full transition-[width]" style={{ width: `${progress.percent}%` }} />
+              : <div className="h-full bg-primary rounded-full animate-pulse" style={{ width: '100%' }} />}
           </div>
-          <span className="text-xs font-data text-muted-foreground tabular-nums">{elapsed}s</span>
+          <span className="text-xs font-data text-muted-foreground tabular-nums">
+            {progress ? `${progress.percent.toFixed(0)}%` : `${elapsed}s`}
+            {progress?.etaSeconds != null && ` · 剩余 ${Math.ceil(progress.etaSeconds)}s`}
+          </span>
+          {window.__TAURI_INTERNALS__ && (
+            <Tooltip delayDuration={200}>
+              <TooltipTrigger asChild>
+                <Button variant="ghost" size="icon" className="h-7 w-7 rounded-lg hover:bg-destructive/10 hover:text-destructive" onClick={handleCancel}>
+                  <Square size={14} fill="currentColor" />
+                </Button>
+              </TooltipTrigger>
+              <TooltipContent side="bottom" className="text-xs rounded-xl">停止仿真</TooltipContent>
+            </Tooltip>
+          )}
         </div>
       )}
 
@@ -1,8 +1,11 @@
 import ReactEChartsCore from 'echarts-for-react';
-import { useState } from 'react';
+import { useEffect, useMemo, useRef, useState } from 'react';
 import { useAppStore } from '../../store/useAppStore';
 import { X, TrendingUp, Download } from 'lucide-react';
 import { toast } from '../../hooks/use-toast';
+import { exportTransientResult } from '../../model/dataBridge';
+import { sliceResult } from '../../utils/engine';
+import type { TransientResult } from '../../types';
 
 type ConcUnit = 'kg/m³' | 'mg/m³' | 'μg/m³' | 'ppm';
 
@@ -33,28 +36,74 @@ const SAFETY_REFS: Record<string, { value: number; label: string; color: string
   'PM2.5': [{ value: 7.5e-5, label: 'PM2.5 75μg/m³', color: '#ef4444' }, { value: 3.5e-5, label: 'PM2.5 35μg/m³', color: '#f59e0b' }],
 };
 
+/** Steps fetched per chart; enough for a chart the width of the screen. */
+const CHART_MAX_POINTS = 2000;
+
+/** Zooming settles for this long before the zoomed window is fetched [ms] */
+const ZOOM_DEBOUNCE_MS = 200;
+
+/** Zoomed part of a result, in percent of its duration */
+interface Zoom {
+  result: TransientResult | null;
+  start: number;
+  end: number;
+}
+
+type ZoomEvent = { start?: number; end?: number; batch?: { start: number; end: number }[] };
+
 export default function TransientChart() {
   const { transientResult, setTransientResult } = useAppStore();
   const [concUnit, setConcUnit] = useState<ConcUnit>('kg/m³');
+  const [zoom, setZoom] = useState<Zoom>({ result: null, start: 0, end: 100 });
+  const [slice, setSlice] = useState<{ result: TransientResult; rows: Float64Array } | null>(null);
+  const zoomTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
+
+  // A new result is shown whole
+  const { start, end } = zoom.result === transientResult ? zoom : { start: 0, end: 100 };
+  const zones = useMemo(() => transientResult?.nodes.filter(n => n.type !== 'ambient') ?? [], [transientResult]);
+  const times = transientResult?.times ?? [];
+  const firstTime = times[0] ?? 0;
+  const lastTime = times[times.length - 1] ?? 0;
+
+  // Only the zoomed window of the zones and species on screen, thinned to what the chart can show
+  useEffect(() => {
+    if (!transientResult) return;
+    let stale = false;
+    const span = lastTime - firstTime;
+    sliceResult(transientResult, {
+      startTime: firstTime + span * start / 100,
+      endTime: firstTime + span * end / 100,
+      nodeIds: zones.map(n => n.id),
+      speciesIds: transientResult.species.map(s => s.id),
+      maxPoints: CHART_MAX_POINTS,
+    })
+      .then((rows) => { if (!stale) setSlice({ result: transientResult, rows }); })
+      .catch((e: unknown) => {
+        if (!stale) toast({ title: '读取结果失败', description: e instanceof Error ? e.message : String(e), variant: 'destructive' });
+      });
+    return () => { stale = true; };
+  }, [transientResult, zones, start, end, firstTime, lastTime]);
 
-  if (!transientResult) return null;
+  useEffect(() => () => { if (zoomTimer.current) clearTimeout(zoomTimer.current); }, []);
 
-  const { timeSeries, nodes, species } = transientResult;
-  if (!timeSeries || timeSeries.length === 0) return null;
+  if (!transientResult || times.length === 0) return null;
 
-  const timeData = timeSeries.map((ts) => (ts.time / 60).toFixed(1));
+  const { species } = transientResult;
+  // Rows of a previous result are laid out for its zones
+  const rows = slice?.result === transientResult ? slice.rows : null;
+  const width = 1 + zones.length + zones.length * species.length;
+  const stepCount = rows ? Math.floor(rows.length / width) : 0;
+  const column = (col: number) =>
+    Array.from({ length: stepCount }, (_, k) => [rows![k * width] / 60, rows![k * width + col]]);
   const colors = ['#7c3aed', '#2563eb', '#059669', '#d97706', '#dc2626', '#6366f1', '#0891b2', '#be185d'];
 
   // Build concentration series with unit conversion
-  const concSeries: { name: string; data: number[]; type: 'line' }[] = [];
-  nodes.forEach((node, nodeIdx) => {
-    if (node.type === 'ambient') return;
+  const concSeries: { name: string; data: number[][]; type: 'line'; showSymbol: false }[] = [];
+  zones.forEach((node, zone) => {
     species.forEach((sp, spIdx) => {
-      const data = timeSeries.map((ts) => {
-        const raw = ts.concentrations?.[nodeIdx]?.[spIdx] ?? 0;
-        return convertConc(raw, concUnit, sp.molarMass);
-      });
-      concSeries.push({ name: `${node.name} - ${sp.name}`, data, type: 'line' });
+      const data = column(1 + zones.length + zone * species.length + spIdx)
+        .map(([t, raw]) => [t, convertConc(raw, concUnit, sp.molarMass)]);
+      concSeries.push({ name: `${node.name} - ${sp.name}`, data, type: 'line', showSymbol: false });
     });
   });
 
@@ -73,12 +122,32 @@ export default function TransientChart() {
   });
 
   // Pressure series
-  const pressureSeries: { name: string; data: number[]; type: 'line' }[] = [];
-  nodes.forEach((node, nodeIdx) => {
-    if (node.type === 'ambient') return;
-    const data = timeSeries.map((ts) => ts.airflow?.pressures?.[nodeIdx] ?? 0);
-    pressureSeries.push({ name: `${node.name} 压力`, data, type: 'line' });
-  });
+  const pressureSeries = zones.map((node, zone) => ({
+    name: `${node.name} 压力`,
+    data: column(1 + zone),
+    type: 'line' as const,
+    showSymbol: false,
+  }));
+
+  // The axis spans the whole run so zoom percentages map to times; the data covers the zoomed window
+  const xAxis = {
+    type: 'value' as const,
+    min: firstTime / 60,
+    max: lastTime / 60,
+    name: '时间 (min)',
+    nameTextStyle: { fontSize: 10 },
+    axisLabel: { fontSize: 9, formatter: (v: number) => v.toFixed(1) },
+  };
+  const dataZoom = [{ type: 'inside' as const, xAxisIndex: 0, start, end, filterMode: 'none' as const }];
+  const onEvents = {
+    datazoom: (e: ZoomEvent) => {
+      const zoomed = e.batch?.[0] ?? e;
+      if (zoomed.start === undefined || zoomed.end === undefined) return;
+      const next = { result: transientResult, start: zoomed.start, end: zoomed.end };
+      if (zoomTimer.current) clearTimeout(zoomTimer.current);
+      zoomTimer.current = setTimeout(() => setZoom(next), ZOOM_DEBOUNCE_MS);
+    },
+  };
 
   const unitLabel = concUnit;
   const yAxisFormatter = (v: number) => {
@@ -92,8 +161,8 @@ export default function TransientChart() {
     tooltip: { trigger: 'axis', textStyle: { fontSize: 11 } },
     legend: { bottom: 0, textStyle: { fontSize: 10 }, itemWidth: 12, itemHeight: 8 },
     grid: { top: 35, right: 20, bottom: 30, left: 65 },
-    dataZoom: [{ type: 'inside', xAxisIndex: 0 }],
-    xAxis: { type: 'category', data: timeData, name: '时间 (min)', nameTextStyle: { fontSize: 10 }, axisLabel: { fontSize: 9 } },
+    dataZoom,
+    xAxis,
     yAxis: { type: 'value', name: `浓度 (${unitLabel})`, nameTextStyle: { fontSize: 10 }, axisLabel: { fontSize: 9, formatter: yAxisFormatter } },
     color: colors,
     series: concSeries.map((s, i) => ({
@@ -114,42 +183,22 @@ export default function TransientChart() {
     tooltip: { trigger: 'axis' as const, textStyle: { fontSize: 11 } },
     legend: { bottom: 0, textStyle: { fontSize: 10 }, itemWidth: 12, itemHeight: 8 },
     grid: { top: 35, right: 20, bottom: 30, left: 55 },
-    dataZoom: [{ type: 'inside' as const, xAxisIndex: 0 }],
-    xAxis: { type: 'category' as const, data: timeData, name: '时间 (min)', nameTextStyle: { fontSize: 10 }, axisLabel: { fontSize: 9 } },
+    dataZoom,
+    xAxis,
     yAxis: { type: 'value' as const, name: '压力 (Pa)', nameTextStyle: { fontSize: 10 }, axisLabel: { fontSize: 9 } },
     color: colors.slice(3),
     series: pressureSeries,
     animation: false,
   };
 
-  const handleExportCSV = () => {
-    const nonAmbient = nodes.filter(nd => nd.type !== 'ambient');
-    const header = [
-      '时间(s)',
-      ...nonAmbient.map(nd => `${nd.name}_压力(Pa)`),
-      ...nonAmbient.flatMap(nd => species.map(sp => `${nd.name}_${sp.name}(kg/m³)`)),
-    ].join(',');
-    const rows = timeSeries.map(ts => {
-      const vals = [ts.time.toString()];
-      nodes.forEach((nd, ni) => {
-        if (nd.type === 'ambient') return;
-        vals.push((ts.airflow?.pressures?.[ni] ?? 0).toFixed(4));
-      });
-      nodes.forEach((nd, ni) => {
-        if (nd.type === 'ambient') return;
-        species.forEach((_, si) => {
-          vals.push((ts.concentrations?.[ni]?.[si] ?? 0).toExponential(6));
-        });
-      });
-      return vals.join(',');
-    });
-    const csv = [header, ...rows].join('\n');
-    const blob = new Blob(['\uFEFF' + csv], { type: 'text/csv;charset=utf-8' });
-    const url = URL.createObjectURL(blob);
-    const a = document.createElement('a');
-    a.href = url; a.download = 'transient_results.csv'; a.click();
-    URL.revokeObjectURL(url);
-    toast({ title: '已导出', description: 'transient_results.csv' });
+  // Written by the backend from the full-resolution result, not from the thinned chart data
+  const handleExportCSV = async () => {
+    try {
+      const path = await exportTransientResult();
+      if (path) toast({ title: '已导出', description: path });
+    } catch (e: unknown) {
+      toast({ title: '导出失败', description: e instanceof Error ? e.message : String(e), variant: 'destructive' });
+    }
   };
 
   return (
@@ -184,12 +233,12 @@ export default function TransientChart() {
       <div className="flex gap-2 p-2 overflow-x-auto" style={{ maxHeight: 280 }}>
         {concSeries.length > 0 && (
           <div className="flex-1 min-w-[350px]">
-            <ReactEChartsCore option={concOption} style={{ height: 240 }} notMerge />
+            <ReactEChartsCore option={concOption} style={{ height: 240 }} notMerge onEvents={onEvents} />
           </div>
         )}
         {pressureSeries.length > 0 && (
           <div className="flex-1 min-w-[350px]">
-            <ReactEChartsCore option={pressureOption} style={{ height: 240 }} notMerge />
+            <ReactEChartsCore option={pressureOption} style={{ height: 240 }} notMerge onEvents={onEvents} />
           </div>
         )}
       </div>
@@ -0,0 +1,58 @@
+// Live mode: while enabled, every model edit is pushed to the backend, which re-solves
+// the steady model once edits pause; each solve replaces the displayed result so the
+// pressure labels and flow arrows follow the edits
+import { useEffect } from 'react';
+import { useAppStore } from '../store/useAppStore';
+import { useCanvasStore } from '../store/useCanvasStore';
+import { canvasToTopology } from '../model/dataBridge';
+import { toast } from './use-toast';
+import { describeEngineError, liveStop, liveUpdate, onLiveSolve } from '../utils/engine';
+
+export function useLiveSolve(enabled: boolean): void {
+  useEffect(() => {
+    if (!enabled || !window.__TAURI_INTERNALS__) return;
+
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+    // Store changes that leave the model as it was (selection, results) are not re-solved
+    let lastInput = '';
+    let lastError = '';
+    const fail = (message: string) => {
+      if (message === lastError) return;
+      lastError = message;
+      toast({ title: '实时求解失败', description: message, variant: 'destructive' });
+    };
+
+    const push = () => {
+      const input = JSON.stringify(canvasToTopology());
+      if (input === lastInput) return;
+      lastInput = input;
+      liveUpdate(input).catch((e: unknown) => fail(e instanceof Error ? e.message : String(e)));
+    };
+
+    onLiveSolve((update) => {
+      if (update.status === 'failed') {
+        fail(describeEngineError(update.error));
+        return;
+      }
+      lastError = '';
+      const { setResult, setTransientResult } = useAppStore.getState();
+      setTransientResult(null);
+      setResult(update.result);
+    }).then((stop) => {
+      if (disposed) stop();
+      else unlisten = stop;
+    });
+
+    push();
+    const unsubCanvas = useCanvasStore.subscribe(push);
+    const unsubApp = useAppStore.subscribe(push);
+    return () => {
+      disposed = true;
+      unsubCanvas();
+      unsubApp();
+      unlisten?.();
+      liveStop().catch(() => {});
+    };
+  }, [enabled]);
+}
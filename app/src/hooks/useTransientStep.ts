@@ -0,0 +1,26 @@
+// Keeps `transientStep` on the step the time stepper points at. Only that step is
+// fetched from the backend, so stepping through a long run never loads the whole
+// result into the webview
+import { useEffect } from 'react';
+import { useAppStore } from '../store/useAppStore';
+import { useCanvasStore } from '../store/useCanvasStore';
+import { loadStep } from '../utils/engine';
+import { toast } from './use-toast';
+
+export function useTransientStep(): void {
+  const transientResult = useAppStore(s => s.transientResult);
+  const currentStep = useCanvasStore(s => s.currentTransientStep);
+
+  useEffect(() => {
+    if (!transientResult || transientResult.times.length === 0) return;
+    // A later step replaces this one if it is asked for before this one arrives
+    let stale = false;
+    const step = Math.min(currentStep, transientResult.times.length - 1);
+    loadStep(transientResult, step)
+      .then((loaded) => { if (!stale) useAppStore.getState().setTransientStep(loaded); })
+      .catch((e: unknown) => {
+        if (!stale) toast({ title: '读取结果失败', description: e instanceof Error ? e.message : String(e), variant: 'destructive' });
+      });
+    return () => { stale = true; };
+  }, [transientResult, currentStep]);
+}
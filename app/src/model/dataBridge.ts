@@ -7,6 +7,7 @@ import { faceArea, getEdge, edgeLength } from './geometry';
 import type { TopologyJson, FlowElementDef } from '../types';
 import { useCanvasStore } from '../store/useCanvasStore';
 import { useAppStore } from '../store/useAppStore';
+import { downloadFile, exportBackendResult } from '../utils/fileOps';
 
 /**
  * Placement type → default flow element definition mapping
@@ -332,7 +333,20 @@ export function steadyResultToCSV(): string {
 }
 
 /**
- * Export transient results as CSV string.
+ * Export transient results: a run's result is written by the backend from its
+ * own copy, demo data is built into a CSV here. Returns the path written, or
+ * null if the user cancelled.
+ */
+export async function exportTransientResult(): Promise<string | null> {
+  const tr = useAppStore.getState().transientResult;
+  if (!tr) return null;
+  if (tr.runId !== null) return exportBackendResult(tr.runId, 'transient_results.csv');
+  const csv = transientResultToCSV();
+  return csv ? downloadFile(csv, 'transient_results.csv') : null;
+}
+
+/**
+ * Export transient demo data, which is held in the webview, as CSV string.
  */
 export function transientResultToCSV(): string {
   const appState = useAppStore.getState();
@@ -361,7 +375,7 @@ export function transientResultToCSV(): string {
   lines.push(headers.join(','));
 
   // Data rows
-  for (const step of tr.timeSeries) {
+  for (const step of tr.timeSeries ?? []) {
     const row: string[] = [step.time.toFixed(1)];
 
     // Pressures
@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { temporal } from 'zundo';
-import type { AppState, ZoneNode, AirflowLink, TopologyJson, Species, Source, Schedule, TransientResult, Occupant, ControlSystem, AHSConfig, FilterConfig } from '../types';
+import type { AppState, ZoneNode, AirflowLink, TopologyJson, Species, Source, Schedule, TransientResult, TransientTimeStep, Occupant, ControlSystem, AHSConfig, FilterConfig } from '../types';
 
 export const useAppStore = create<AppState>()(temporal((set, get) => ({
   // Model data
@@ -35,6 +35,7 @@ export const useAppStore = create<AppState>()(temporal((set, get) => ({
   // Simulation
   result: null,
   transientResult: null,
+  transientStep: null,
   isRunning: false,
   error: null,
 
@@ -151,7 +152,8 @@ export const useAppStore = create<AppState>()(temporal((set, get) => ({
   setTransientConfig: (config) => set((state) => ({
     transientConfig: { ...state.transientConfig, ...config },
   })),
-  setTransientResult: (result: TransientResult | null) => set({ transientResult: result }),
+  setTransientResult: (result: TransientResult | null) => set({ transientResult: result, transientStep: null }),
+  setTransientStep: (step: TransientTimeStep | null) => set({ transientStep: step }),
 
   setWeatherConfig: (config) => set((state) => ({
     weatherConfig: { ...state.weatherConfig, ...config },
@@ -271,6 +273,7 @@ export const useAppStore = create<AppState>()(temporal((set, get) => ({
       filterConfigs: [],
       result: null,
       transientResult: null,
+      transientStep: null,
       error: null,
       nextId: maxId + 1,
     };
@@ -291,6 +294,7 @@ export const useAppStore = create<AppState>()(temporal((set, get) => ({
     filterConfigs: [],
     result: null,
     transientResult: null,
+    transientStep: null,
     error: null,
     nextId: 1,
   }),
@@ -156,12 +156,22 @@ export interface TransientTimeStep {
   concentrations: number[][];  // [nodeIdx][speciesIdx]
 }
 
+/**
+ * A transient result as the views hold it: what it contains and its output
+ * times. Series stay in the backend and are fetched in slices (`sliceResult`).
+ */
 export interface TransientResult {
+  /** Result in the backend; `null` for demo data, which is held in `timeSeries` */
+  runId: string | null;
   completed: boolean;
   totalSteps: number;
   species: { id: number; name: string; molarMass: number }[];
   nodes: { id: number; name: string; type: string }[];
-  timeSeries: TransientTimeStep[];
+  linkCount: number;
+  /** Output time of every step [s] */
+  times: number[];
+  /** Demo data only */
+  timeSeries?: TransientTimeStep[];
 }
 
 // ── Topology JSON (matches engine schema) ────────────────────────────
@@ -366,6 +376,8 @@ export interface AppState {
   // Simulation
   result: SimulationResult | null;
   transientResult: TransientResult | null;
+  /** The transient step at the canvas's current time, fetched by `useTransientStep` */
+  transientStep: TransientTimeStep | null;
   isRunning: boolean;
   error: string | null;
 
@@ -404,6 +416,7 @@ export interface AppState {
   setControlSystem: (cs: ControlSystem) => void;
   setTransientConfig: (config: Partial<TransientConfig>) => void;
   setTransientResult: (result: TransientResult | null) => void;
+  setTransientStep: (step: TransientTimeStep | null) => void;
   setWeatherConfig: (config: Partial<WeatherConfig>) => void;
   addAHS: (ahs: AHSConfig) => void;
   updateAHS: (id: number, updates: Partial<AHSConfig>) => void;
@@ -0,0 +1,481 @@
+/**
+ * Engine run control — starts `contam_engine` through the Tauri backend and
+ * resolves once the matching `engine-run-finished` event arrives.
+ */
+
+import type { SimulationResult, TransientResult, TransientTimeStep } from '../types';
+
+export interface EngineLogs {
+  stdout: string;
+  stderr: string;
+  exitCode: number | null;
+}
+
+/** Mirrors `SchemaViolation` in `src-tauri/src/validation.rs`. */
+export interface SchemaViolation {
+  /** JSON pointer into the input, e.g. `/links/3/element/type` */
+  pointer: string;
+  message: string;
+}
+
+/** Mirrors `EngineError` in `src-tauri/src/error.rs`. */
+export type EngineError =
+  | { kind: 'invalidInput'; errors: SchemaViolation[]; logs: EngineLogs }
+  | { kind: 'engineNotFound'; path: string; logs: EngineLogs }
+  | { kind: 'spawnFailed'; path: string; message: string; logs: EngineLogs }
+  | { kind: 'inputError'; logs: EngineLogs }
+  | { kind: 'notConverged'; logs: EngineLogs }
+  | { kind: 'outputMissing'; message: string; logs: EngineLogs }
+  | { kind: 'invalidOutput'; message: string; logs: EngineLogs }
+  | { kind: 'timedOut'; timeoutSecs: number; logs: EngineLogs }
+  | { kind: 'outOfMemory'; memoryLimitMb: number | null; logs: EngineLogs }
+  | { kind: 'crashed'; logs: EngineLogs }
+  | { kind: 'io'; message: string; logs: EngineLogs };
+
+/** Mirrors `RetryPolicy` in `src-tauri/src/retry.rs`. */
+export interface RetryPolicy {
+  /** Also give a transient model's retry more airflow iterations and stronger under-relaxation */
+  relaxTransient?: boolean;
+}
+
+/** Mirrors `SolveAttempt` in `src-tauri/src/retry.rs`: one engine invocation of a retried run. */
+export interface SolveAttempt {
+  method: 'tr' | 'sur';
+  relaxed: boolean;
+  converged: boolean;
+  /** Steady solver iterations, or the most any transient step needed */
+  iterations: number | null;
+  /** [kg/s] */
+  maxResidual: number | null;
+  error: string | null;
+  /** This attempt produced the returned result */
+  selected: boolean;
+}
+
+/**
+ * Mirrors `RunSummary` in `src-tauri/src/summary.rs`: what the engine printed
+ * about the run. Fields are null when the line was not printed.
+ */
+export interface RunSummary {
+  nodeCount: number | null;
+  linkCount: number | null;
+  unknownCount: number | null;
+  speciesCount: number | null;
+  sourceCount: number | null;
+  /** Printed for steady solves only */
+  method: 'tr' | 'sur' | null;
+  converged: boolean | null;
+  iterations: number | null;
+  /** [kg/s] */
+  maxResidual: number | null;
+  /** Transient window [s] */
+  startTime: number | null;
+  endTime: number | null;
+  timeStep: number | null;
+  outputSteps: number | null;
+}
+
+/**
+ * A finished run. `partial` is set when the steady solve did not converge or
+ * the transient run stopped early; the output then holds the last state.
+ * The output stays in the backend: fetch it with `loadResult` and `sliceResult`.
+ */
+export interface RunResult {
+  runId: string;
+  partial: boolean;
+  maxResidual: number | null;
+  summary: RunSummary;
+  logs: EngineLogs;
+  /** Run in the history this result was reused from, when the engine was not spawned */
+  cachedRunId: string | null;
+  /** Every solve when a retry policy was given; empty otherwise */
+  attempts: SolveAttempt[];
+}
+
+type RunFinished =
+  | ({ status: 'completed' } & RunResult)
+  | { runId: string; status: 'failed'; error: EngineError }
+  | { runId: string; status: 'cancelled' };
+
+/** Thrown by `runEngine` when the backend reports a failed run. */
+export class EngineRunError extends Error {
+  readonly error: EngineError;
+
+  constructor(error: EngineError) {
+    super(describeEngineError(error));
+    this.name = 'EngineRunError';
+    this.error = error;
+  }
+}
+
+/** User-facing message for each failure kind, with the engine's own error line when available. */
+export function describeEngineError(error: EngineError): string {
+  const detail = error.logs.stderr.trim();
+  switch (error.kind) {
+    case 'invalidInput': {
+      const lines = error.errors.slice(0, 5).map(e => `${e.pointer || '/'}：${e.message}`);
+      const more = error.errors.length > 5 ? `\n……另有 ${error.errors.length - 5} 处` : '';
+      return `模型数据不符合拓扑格式要求：\n${lines.join('\n')}${more}`;
+    }
+    case 'engineNotFound': return `未找到仿真引擎：${error.path}`;
+    case 'spawnFailed': return `无法启动仿真引擎：${error.message}`;
+    case 'inputError': return `模型输入有误，引擎无法解析${detail ? `：${detail}` : ''}`;
+    case 'notConverged': return '求解未收敛，可尝试改用亚松弛（SUR）求解器或检查模型中的气流路径参数';
+    case 'outputMissing': return `引擎未生成结果文件：${error.message}`;
+    case 'invalidOutput': return `结果文件格式无效：${error.message}`;
+    case 'timedOut': return `仿真超时（${error.timeoutSecs} 秒），引擎进程已终止`;
+    case 'outOfMemory':
+      return error.memoryLimitMb != null
+        ? `引擎内存超出上限（${error.memoryLimitMb} MB），进程已终止`
+        : '引擎内存不足，进程已终止';
+    case 'crashed': return `引擎异常退出（退出码 ${error.logs.exitCode ?? '无'}）${detail ? `：${detail}` : ''}`;
+    case 'io': return error.message;
+  }
+}
+
+export interface RunProgress {
+  runId: string;
+  time: number;
+  endTime: number;
+  percent: number;
+  etaSeconds: number | null;
+}
+
+/** Mirrors `SegmentPolicy` in `src-tauri/src/segments.rs`. */
+export interface SegmentPolicy {
+  /** Simulated time per segment [s], rounded up to whole output intervals */
+  length: number;
+}
+
+/** A finished segment of a segmented run; `resultId` is passed to `getResult`. */
+export interface RunSegment {
+  runId: string;
+  /** Holds the latest segment of the run until the next one finishes */
+  resultId: string;
+  index: number;
+  count: number;
+  startTime: number;
+  endTime: number;
+  /** Read from the checkpoint of an earlier, interrupted run */
+  resumed: boolean;
+}
+
+/** Mirrors `RunOptions` in `src-tauri/src/options.rs`. */
+export interface RunOptions {
+  /** Trust region (default) or sub-relaxation, the usual fallback for stiff networks */
+  method?: 'tr' | 'sur';
+  /** Absolute path; requires an engine built with HDF5 support */
+  hdf5Output?: string | null;
+  /** `quiet` disables progress events */
+  verbosity?: 'quiet' | 'verbose';
+}
+
+export interface RunCallbacks {
+  options?: RunOptions;
+  /** Always spawn the engine, even if an identical run is in the history */
+  fresh?: boolean;
+  /** Solve again with the other method when the solve does not converge */
+  retry?: RetryPolicy;
+  /** Start from the pressures of the latest converged run of the same network */
+  warmStart?: boolean;
+  /**
+   * Run a transient window in segments that are checkpointed as they finish;
+   * a cancelled run keeps the finished segments as a partial result
+   */
+  segments?: SegmentPolicy;
+  /** Receives the backend run ID as soon as the engine has been spawned. */
+  onStarted?: (runId: string) => void;
+  /** Receives throttled progress updates parsed from the engine's verbose output. */
+  onProgress?: (progress: RunProgress) => void;
+  /** Receives each segment of a segmented run as it finishes. */
+  onSegment?: (segment: RunSegment) => void;
+}
+
+export class RunCancelledError extends Error {
+  constructor() {
+    super('仿真已取消');
+    this.name = 'RunCancelledError';
+  }
+}
+
+/** Start a run and resolve once it has finished. */
+export async function runEngine(
+  input: string,
+  { options, fresh, retry, warmStart, segments, onStarted, onProgress, onSegment }: RunCallbacks = {},
+): Promise<RunResult> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  const { listen } = await import('@tauri-apps/api/event');
+
+  // Subscribe before invoking: a fast run can finish before invoke() returns its ID
+  const finished = new Map<string, RunFinished>();
+  let runId: string | null = null;
+  let settle: ((e: RunFinished) => void) | null = null;
+  const unlisten = await listen<RunFinished>('engine-run-finished', (event) => {
+    if (runId === null) finished.set(event.payload.runId, event.payload);
+    else if (event.payload.runId === runId) settle?.(event.payload);
+  });
+  const unlistenProgress = await listen<RunProgress>('engine-run-progress', (event) => {
+    if (event.payload.runId === runId) onProgress?.(event.payload);
+  });
+  const unlistenSegment = await listen<RunSegment>('engine-run-segment', (event) => {
+    if (event.payload.runId === runId) onSegment?.(event.payload);
+  });
+
+  try {
+    runId = await invoke<string>('run_engine', { input, options, fresh, retry, warmStart, segments });
+    onStarted?.(runId);
+    const id = runId;
+    const result = finished.get(id) ?? await new Promise<RunFinished>((resolve) => { settle = resolve; });
+    switch (result.status) {
+      case 'completed': {
+        const { partial, maxResidual, summary, logs, cachedRunId, attempts } = result;
+        return { runId: id, partial, maxResidual, summary, logs, cachedRunId, attempts };
+      }
+      case 'failed': throw new EngineRunError(result.error);
+      case 'cancelled': throw new RunCancelledError();
+    }
+  } finally {
+    unlisten();
+    unlistenProgress();
+    unlistenSegment();
+  }
+}
+
+/** Mirrors `SliceQuery` in `src-tauri/src/results.rs`. */
+export interface SliceQuery {
+  /** [s]; unbounded if omitted */
+  startTime?: number;
+  endTime?: number;
+  nodeIds?: number[];
+  speciesIds?: number[];
+  /** Positions in the input's `links` */
+  linkIndices?: number[];
+  /** Thin the window to about this many evenly spaced steps */
+  maxPoints?: number;
+  /** Include each step's airflow solve as `converged` (1 or 0) and `iterations` after the time */
+  solver?: boolean;
+}
+
+/** Mirrors `ResultOverview` in `src-tauri/src/result_store.rs`. */
+export type ResultOverview =
+  | ({ kind: 'steady' } & SimulationResult)
+  | {
+      kind: 'transient';
+      completed: boolean;
+      totalSteps: number;
+      species: TransientResult['species'];
+      nodes: TransientResult['nodes'];
+      linkCount: number;
+      startTime: number | null;
+      endTime: number | null;
+    };
+
+export async function getResult(runId: string): Promise<ResultOverview> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ResultOverview>('get_result', { runId });
+}
+
+/**
+ * Part of a transient result, one row per step:
+ * `[time, (converged, iterations,) pressures.., massFlows.., concentrations..]`
+ * with the columns in query order and concentrations node-major.
+ */
+export async function getResultSlice(runId: string, query: SliceQuery): Promise<Float64Array> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  const bytes = await invoke<ArrayBuffer>('get_result_slice', { runId, query });
+  return new Float64Array(bytes);
+}
+
+/**
+ * A run's result for the result views: steady results whole, transient ones
+ * as what they contain and their output times. Transient series are fetched
+ * as they are shown, with `sliceResult` and `loadStep`.
+ */
+export async function loadResult(runId: string): Promise<SimulationResult | TransientResult> {
+  const overview = await getResult(runId);
+  if (overview.kind === 'steady') {
+    const { solver, nodes, links } = overview;
+    return { solver, nodes, links };
+  }
+  const { completed, totalSteps, species, nodes, linkCount } = overview;
+  // A slice without columns is the time of each step
+  const times = Array.from(await getResultSlice(runId, {}));
+  return { runId, completed, totalSteps, species, nodes, linkCount, times };
+}
+
+/** `getResultSlice` for a result in the backend, or the same slice of demo data. */
+export async function sliceResult(result: TransientResult, query: SliceQuery): Promise<Float64Array> {
+  if (result.runId !== null) return getResultSlice(result.runId, query);
+  return sliceSteps(result, result.timeSeries ?? [], query);
+}
+
+/** Every node, link and species at one output step. */
+export async function loadStep(result: TransientResult, step: number): Promise<TransientTimeStep | null> {
+  const time = result.times[step];
+  if (time === undefined) return null;
+  const { nodes, species, linkCount } = result;
+  const values = await sliceResult(result, {
+    startTime: time,
+    endTime: time,
+    nodeIds: nodes.map(n => n.id),
+    speciesIds: species.map(s => s.id),
+    linkIndices: Array.from({ length: linkCount }, (_, i) => i),
+    solver: true,
+  });
+  if (values.length === 0) return null;
+  const concAt = 3 + nodes.length + linkCount;
+  return {
+    time: values[0],
+    airflow: {
+      converged: values[1] !== 0,
+      iterations: values[2],
+      pressures: Array.from(values.subarray(3, 3 + nodes.length)),
+      massFlows: Array.from(values.subarray(3 + nodes.length, concAt)),
+    },
+    concentrations: species.length > 0
+      ? nodes.map((_, n) => Array.from(values.subarray(concAt + n * species.length, concAt + (n + 1) * species.length)))
+      : [],
+  };
+}
+
+/** `TransientResult::slice` in `src-tauri/src/results.rs`, for demo data held in the webview. */
+function sliceSteps(result: TransientResult, steps: TransientTimeStep[], query: SliceQuery): Float64Array {
+  const nodes = (query.nodeIds ?? []).map(id => result.nodes.findIndex(n => n.id === id));
+  const species = (query.speciesIds ?? []).map(id => result.species.findIndex(s => s.id === id));
+  const links = query.linkIndices ?? [];
+  const window = steps.filter(s => s.time >= (query.startTime ?? -Infinity) && s.time <= (query.endTime ?? Infinity));
+  const stride = query.maxPoints && window.length > query.maxPoints ? Math.ceil(window.length / query.maxPoints) : 1;
+  const kept = window.filter((_, i) => i % stride === 0 || i === window.length - 1);
+  const values: number[] = [];
+  for (const step of kept) {
+    values.push(step.time);
+    if (query.solver) values.push(step.airflow.converged ? 1 : 0, step.airflow.iterations);
+    for (const n of nodes) values.push(step.airflow.pressures[n] ?? NaN);
+    for (const l of links) values.push(step.airflow.massFlows[l] ?? NaN);
+    for (const n of nodes) for (const sp of species) values.push(step.concentrations[n]?.[sp] ?? NaN);
+  }
+  return Float64Array.from(values);
+}
+
+/**
+ * Write a run's result to `path` as CSV, XLSX or SQLite, by extension. The
+ * backend writes it from its own copy; nothing passes through the webview.
+ */
+export async function exportResult(runId: string, path: string): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('export_result', { runId, path });
+}
+
+export async function cancelRun(runId: string): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('cancel_run', { runId });
+}
+
+/** Mirrors `LiveUpdate` in `src-tauri/src/live.rs`: one live solve of a steady model. */
+export type LiveUpdate = { revision: number; runId: string | null } & (
+  | { status: 'solved'; partial: boolean; result: SimulationResult }
+  | { status: 'failed'; error: EngineError }
+);
+
+/**
+ * Live mode: queue an edited steady model to be solved once edits pause.
+ * Resolves to the edit's revision; the solve arrives through `onLiveSolve`.
+ */
+export async function liveUpdate(input: string, options?: RunOptions): Promise<number> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<number>('live_update', { input, options });
+}
+
+/** Leave live mode, cancelling the live solve in flight. */
+export async function liveStop(): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('live_stop');
+}
+
+/** Receive every live solve that was not superseded by a later edit. Resolves to the unsubscribe function. */
+export async function onLiveSolve(handler: (update: LiveUpdate) => void): Promise<() => void> {
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<LiveUpdate>('live-solve', (event) => handler(event.payload));
+}
+
+/** Mirrors `EngineInfo` in `src-tauri/src/discovery.rs`. */
+export interface EngineInfo {
+  path: string;
+  source: 'env' | 'settings' | 'bundled' | 'path';
+  found: boolean;
+  version: string | null;
+  hdf5: boolean;
+  compatible: boolean;
+  minVersion: string;
+  error: string | null;
+}
+
+export async function engineInfo(): Promise<EngineInfo> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<EngineInfo>('engine_info');
+}
+
+/** Mirrors `HistoryEntry` in `src-tauri/src/history.rs`. */
+export interface HistoryEntry {
+  runId: string;
+  startedAt: number;
+  durationMs: number;
+  state: 'completed' | 'failed' | 'cancelled' | 'timedOut' | 'outOfMemory';
+  partial: boolean;
+  error: string | null;
+  engineVersion: string | null;
+  options: RunOptions;
+  /** Run whose pressures were the initial guess, if warm-started */
+  warmStartRunId: string | null;
+  attempts: SolveAttempt[];
+  summary: RunSummary;
+  notes: string;
+  tags: string[];
+  sizeBytes: number;
+}
+
+/** A stored run; its result is fetched with `loadResult(runId)`. */
+export interface HistoryRun extends HistoryEntry {
+  input: string;
+  hasOutput: boolean;
+  logs: EngineLogs | null;
+}
+
+export async function listHistory(): Promise<HistoryEntry[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<HistoryEntry[]>('list_history');
+}
+
+export async function loadHistoryRun(runId: string): Promise<HistoryRun> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<HistoryRun>('load_history_run', { runId });
+}
+
+/** Omitted fields are left unchanged. */
+export async function tagHistoryRun(runId: string, update: { notes?: string; tags?: string[] }): Promise<HistoryEntry> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<HistoryEntry>('tag_history_run', { runId, ...update });
+}
+
+export async function deleteHistoryRun(runId: string): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('delete_history_run', { runId });
+}
+
+/** Returns the IDs of the deleted runs. */
+export async function pruneHistory(quotaMb?: number): Promise<string[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string[]>('prune_history', { quotaMb });
+}
+
+/** Tail of the backend's application log, including every engine invocation. */
+export async function readLogs(maxBytes?: number): Promise<string> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string>('read_logs', { maxBytes });
+}
+
+/** Check a topology against the backend's schema without running it; empty when valid. */
+export async function validateInput(input: string): Promise<SchemaViolation[]> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<SchemaViolation[]>('validate_input', { input });
+}
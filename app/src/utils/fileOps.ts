@@ -3,6 +3,8 @@
  * falls back to browser APIs otherwise.
  */
 
+import { exportResult } from './engine';
+
 const isTauri = () => !!(window as unknown as Record<string, unknown>).__TAURI_INTERNALS__;
 
 // ── Save ────────────────────────────────────────────────────────────
@@ -84,3 +86,23 @@ export async function downloadFile(
 ): Promise<string | null> {
   return saveFile(content, defaultName, filters);
 }
+
+// ── Export of results kept in the backend ───────────────────────────
+
+const RESULT_FILTERS = [
+  { name: 'CSV', extensions: ['csv'] },
+  { name: 'Excel', extensions: ['xlsx'] },
+  { name: 'SQLite', extensions: ['sqlite'] },
+];
+
+/**
+ * Ask where to save a run's result and have the backend write it there, in
+ * the format of the chosen extension. Returns the path, or null if cancelled.
+ */
+export async function exportBackendResult(runId: string, defaultName: string): Promise<string | null> {
+  const { save } = await import('@tauri-apps/plugin-dialog');
+  const path = await save({ defaultPath: defaultName, filters: RESULT_FILTERS });
+  if (!path) return null;
+  await exportResult(runId, path);
+  return path;
+}
@@ -16,6 +16,8 @@ TransientResult TransientSimulation::run(Network& network) {
 
     // Initialize airflow solver
     Solver airflowSolver(config_.airflowMethod);
+    if (config_.airflowMaxIterations > 0) airflowSolver.setMaxIterations(config_.airflowMaxIterations);
+    if (config_.airflowRelaxFactor > 0.0) airflowSolver.setRelaxFactor(config_.airflowRelaxFactor);
 
     // Initialize contaminant solver
     ContaminantSolver contSolver;
@@ -26,6 +28,14 @@ TransientResult TransientSimulation::run(Network& network) {
         contSolver.setSources(sources_);
         contSolver.setSchedules(schedules_);
         contSolver.initialize(network);
+        for (const auto& [nodeIdx, bySpecies] : initialConcentrations_) {
+            for (int k = 0; k < static_cast<int>(species_.size()); ++k) {
+                auto it = bySpecies.find(species_[k].id);
+                if (it != bySpecies.end()) {
+                    contSolver.setInitialConcentration(nodeIdx, k, it->second);
+                }
+            }
+        }
     }
 
     double t = config_.startTime;
@@ -23,6 +23,8 @@ struct TransientConfig {
     double timeStep = 60.0;      // s
     double outputInterval = 60.0; // s (how often to record results)
     SolverMethod airflowMethod = SolverMethod::TrustRegion;
+    int airflowMaxIterations = 0;    // per airflow solve; 0 = MAX_ITERATIONS
+    double airflowRelaxFactor = 0.0; // SUR relaxation factor; 0 = RELAX_FACTOR_SUR
 };
 
 struct TimeStepResult {
@@ -51,6 +53,11 @@ public:
     void setSources(const std::vector<Source>& sources) { sources_ = sources; }
     void setSchedules(const std::map<int, Schedule>& schedules) { schedules_ = schedules; }
 
+    // Starting concentrations: maps node index -> species ID -> kg/m³ (default 0, outdoor for ambient)
+    void setInitialConcentrations(const std::map<int, std::map<int, double>>& initial) {
+        initialConcentrations_ = initial;
+    }
+
     // Control system
     void setSensors(const std::vector<Sensor>& sensors) { sensors_ = sensors; }
     void setControllers(const std::vector<Controller>& controllers) { controllers_ = controllers; }
@@ -99,6 +106,7 @@ private:
     std::vector<Actuator> actuators_;
     std::vector<Occupant> occupants_;
     std::map<int, int> zoneTempSchedules_;  // nodeIdx -> scheduleId
+    std::map<int, std::map<int, double>> initialConcentrations_;  // nodeIdx -> speciesId -> kg/m³
     std::vector<WeatherRecord> weatherData_;
     std::vector<SimpleAHS> ahSystems_;
     std::map<int, Schedule> externalSchedules_;
@@ -339,6 +339,18 @@ ModelInput JsonReader::readModelFromString(const std::string& jsonStr) {
         }
     }
 
+    // Parse per-node initial concentrations: { "<speciesId>": kg/m³ }
+    if (j.contains("nodes")) {
+        for (auto& jNode : j["nodes"]) {
+            if (!jNode.contains("initialConcentrations")) continue;
+            int nodeIdx = model.network.getNodeIndexById(jNode["id"].get<int>());
+            if (nodeIdx < 0) continue;
+            for (auto& [speciesId, conc] : jNode["initialConcentrations"].items()) {
+                model.initialConcentrations[nodeIdx][std::stoi(speciesId)] = conc.get<double>();
+            }
+        }
+    }
+
     // Parse transient config
     if (j.contains("transient")) {
         model.hasTransient = true;
@@ -351,6 +363,8 @@ ModelInput JsonReader::readModelFromString(const std::string& jsonStr) {
         if (method == "subRelaxation") {
             model.transientConfig.airflowMethod = SolverMethod::SubRelaxation;
         }
+        model.transientConfig.airflowMaxIterations = jt.value("airflowMaxIterations", 0);
+        model.transientConfig.airflowRelaxFactor = jt.value("airflowRelaxFactor", 0.0);
     }
 
     // Parse weather data
@@ -19,6 +19,7 @@ struct ModelInput {
     std::vector<Source> sources;
     std::map<int, Schedule> schedules;
     std::map<int, int> zoneTemperatureSchedules;  // nodeIdx -> scheduleId
+    std::map<int, std::map<int, double>> initialConcentrations;  // nodeIdx -> speciesId -> kg/m³
     TransientConfig transientConfig;
     bool hasTransient = false;
     std::vector<WeatherRecord> weatherData;
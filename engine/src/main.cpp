@@ -10,7 +10,7 @@
 #include <string>
 
 void printUsage(const char* progName) {
-    std::cout << "AirSim Studio Engine v0.2.0\n"
+    std::cout << "AirSim Studio Engine v0.4.0\n"
               << "Usage: " << progName << " -i <input.json> -o <output.json> [options]\n"
               << "\nOptions:\n"
               << "  -i <file>    Input JSON file (required)\n"
@@ -99,6 +99,7 @@ int main(int argc, char* argv[]) {
             sim.setSpecies(model.species);
             sim.setSources(model.sources);
             sim.setSchedules(model.schedules);
+            sim.setInitialConcentrations(model.initialConcentrations);
             sim.setZoneTemperatureSchedules(model.zoneTemperatureSchedules);
             sim.setOccupants(model.occupants);
             if (!model.weatherData.empty()) {
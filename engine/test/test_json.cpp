@@ -118,6 +118,72 @@ TEST(JsonReaderTest, InlineFlowElement) {
     EXPECT_NE(network.getLink(0).getFlowElement(), nullptr);
 }
 
+TEST(JsonReaderTest, InitialConcentrations) {
+    std::string jsonStr = R"({
+        "nodes": [
+            {"id": 0, "name": "Out", "type": "ambient"},
+            {"id": 5, "name": "Room", "volume": 50.0, "initialConcentrations": {"2": 0.001}}
+        ],
+        "links": [
+            {
+                "id": 1, "from": 0, "to": 5, "elevation": 1.0,
+                "element": {"type": "PowerLawOrifice", "C": 0.002, "n": 0.6}
+            }
+        ],
+        "species": [{"id": 2, "name": "CO2", "molarMass": 0.044}],
+        "transient": {"startTime": 0, "endTime": 60, "timeStep": 60, "outputInterval": 60}
+    })";
+
+    auto model = JsonReader::readModelFromString(jsonStr);
+    int room = model.network.getNodeIndexById(5);
+    ASSERT_EQ(model.initialConcentrations.count(room), 1u);
+    EXPECT_DOUBLE_EQ(model.initialConcentrations[room][2], 0.001);
+
+    TransientSimulation sim;
+    sim.setConfig(model.transientConfig);
+    sim.setSpecies(model.species);
+    sim.setInitialConcentrations(model.initialConcentrations);
+    auto result = sim.run(model.network);
+
+    // The recorded initial state starts from the given concentration
+    ASSERT_FALSE(result.history.empty());
+    EXPECT_DOUBLE_EQ(result.history[0].contaminant.concentrations[room][0], 0.001);
+}
+
+TEST(JsonReaderTest, TransientAirflowSettings) {
+    auto transientModel = [](const json& transient) {
+        json j = json::parse(SAMPLE_JSON);
+        j["transient"] = transient;
+        return JsonReader::readModelFromString(j.dump());
+    };
+    json window = {{"startTime", 0}, {"endTime", 60}, {"timeStep", 60}, {"outputInterval", 60}};
+
+    auto defaults = transientModel(window);
+    EXPECT_EQ(defaults.transientConfig.airflowMaxIterations, 0);
+    EXPECT_DOUBLE_EQ(defaults.transientConfig.airflowRelaxFactor, 0.0);
+
+    json capped = window;
+    capped["airflowMaxIterations"] = 1;
+    capped["airflowRelaxFactor"] = 0.5;
+    auto model = transientModel(capped);
+    EXPECT_EQ(model.transientConfig.airflowMaxIterations, 1);
+    EXPECT_DOUBLE_EQ(model.transientConfig.airflowRelaxFactor, 0.5);
+
+    // The settings reach the airflow solver: one iteration cannot converge this network
+    TransientSimulation sim;
+    sim.setConfig(model.transientConfig);
+    auto result = sim.run(model.network);
+    ASSERT_FALSE(result.history.empty());
+    EXPECT_LE(result.history[0].airflow.iterations, 1);
+    EXPECT_FALSE(result.history[0].airflow.converged);
+
+    TransientSimulation unlimited;
+    unlimited.setConfig(defaults.transientConfig);
+    auto converged = unlimited.run(defaults.network);
+    ASSERT_FALSE(converged.history.empty());
+    EXPECT_TRUE(converged.history[0].airflow.converged);
+}
+
 TEST(JsonWriterTest, OutputHasCorrectStructure) {
     auto network = JsonReader::readFromString(SAMPLE_JSON);
 